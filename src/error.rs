@@ -0,0 +1,202 @@
+use constant_product_curve::CurveError;
+use pinocchio::error::ProgramError;
+
+/// ========== AMM 自定义错误 ==========
+///
+/// AMM 合约返回的所有业务错误。每个变体都会转换为 `ProgramError::Custom(code)`，
+/// 错误码一经发布即保持稳定，客户端可以通过 [`AmmError::from_code`] 或
+/// [`error_message`] 将链上返回的错误码还原为可读信息。
+///
+/// 错误码 0–2 沿用引入本枚举之前 `ProgramError::Custom` 的取值，新增错误从 3 开始编号。
+///
+/// | 错误码 | 变体 | 含义 |
+/// |------|------|------|
+/// | 0 | `Expired` | 交易已超过指定的过期时间 |
+/// | 1 | `RentCalculationFailed` | 计算 Config 账户租金豁免所需 lamports 失败 |
+/// | 2 | `MintRentCalculationFailed` | 计算 LP mint 账户租金豁免所需 lamports 失败 |
+/// | 3 | `SlippageExceeded` | 实际成交数量超出滑点保护范围 |
+/// | 4 | `InvalidVault` | 金库地址不是 Config 的关联代币账户 |
+/// | 5 | `InvalidVaultOwner` | 金库代币账户的所有者不是 Config |
+/// | 6 | `InvalidMint` | 代币账户持有的 mint 与池配置不符 |
+/// | 7 | `InvalidTokenAccountOwner` | 用户代币账户不属于签名用户 |
+/// | 8 | `PoolDisabled` | 池当前状态不允许该操作 |
+/// | 9 | `ZeroAmount` | 数量参数或计算结果为 0 |
+/// | 10 | `CurveError` | 常数乘积曲线计算失败（溢出、余额不足等） |
+/// | 11 | `InvalidMintLp` | LP mint 不是本池的 LP mint PDA |
+/// | 12 | `InvalidLockedLp` | 锁定 LP 账户不是 Config 的 LP 关联代币账户 |
+/// | 13 | `IdenticalMints` | 代币 X 与代币 Y 的 mint 相同 |
+/// | 14 | `InvalidFee` | 费率超出允许范围 |
+/// | 15 | `InvalidState` | 无效的 AMM 状态值 |
+/// | 16 | `InsufficientInitialLiquidity` | 首次存入的 LP 数量不足以覆盖最小流动性 |
+/// | 17 | `ConfigLocked` | 池未设置权限管理员，配置已永久锁定 |
+/// | 18 | `Unauthorized` | 签名者不是池的权限管理员 |
+/// | 19 | `NoPendingAuthority` | 没有待接受的权限管理员转移 |
+/// | 20 | `InvalidPda` | 账户地址或 bump 不是 canonical PDA |
+/// | 21 | `UnsortedMints` | mint_x 必须按字节序小于 mint_y |
+/// | 22 | `UnsupportedMintExtension` | mint 带有不支持或危险的 Token-2022 扩展 |
+/// | 23 | `EmptyPool` | 池中尚无流动性，无法按池价格计算 |
+/// | 24 | `FlashLoanActive` | 闪电贷进行中，暂不允许该操作 |
+/// | 25 | `FlashLoanNotRepaid` | 闪电贷结束时金库余额未恢复到借出前余额加费用 |
+/// | 26 | `InvalidRoute` | 路由中相邻两跳的代币不衔接，或同一个池出现多次 |
+/// | 27 | `TooManyAccounts` | 传给回调程序的账户数量超过上限 |
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum AmmError {
+    /// 交易已超过指定的过期时间
+    Expired = 0,
+    /// 计算 Config 账户租金豁免所需 lamports 失败
+    RentCalculationFailed = 1,
+    /// 计算 LP mint 账户租金豁免所需 lamports 失败
+    MintRentCalculationFailed = 2,
+    /// 实际成交数量超出滑点保护范围
+    SlippageExceeded = 3,
+    /// 金库地址不是 Config 的关联代币账户
+    InvalidVault = 4,
+    /// 金库代币账户的所有者不是 Config
+    InvalidVaultOwner = 5,
+    /// 代币账户持有的 mint 与池配置不符
+    InvalidMint = 6,
+    /// 用户代币账户不属于签名用户
+    InvalidTokenAccountOwner = 7,
+    /// 池当前状态不允许该操作
+    PoolDisabled = 8,
+    /// 数量参数或计算结果为 0
+    ZeroAmount = 9,
+    /// 常数乘积曲线计算失败（溢出、余额不足等）
+    CurveError = 10,
+    /// LP mint 不是本池的 LP mint PDA
+    InvalidMintLp = 11,
+    /// 锁定 LP 账户不是 Config 的 LP 关联代币账户
+    InvalidLockedLp = 12,
+    /// 代币 X 与代币 Y 的 mint 相同
+    IdenticalMints = 13,
+    /// 费率超出允许范围
+    InvalidFee = 14,
+    /// 无效的 AMM 状态值
+    InvalidState = 15,
+    /// 首次存入的 LP 数量不足以覆盖最小流动性
+    InsufficientInitialLiquidity = 16,
+    /// 池未设置权限管理员，配置已永久锁定
+    ConfigLocked = 17,
+    /// 签名者不是池的权限管理员
+    Unauthorized = 18,
+    /// 没有待接受的权限管理员转移
+    NoPendingAuthority = 19,
+    /// 账户地址或 bump 不是 canonical PDA
+    InvalidPda = 20,
+    /// mint_x 必须按字节序小于 mint_y
+    UnsortedMints = 21,
+    /// mint 带有不支持或危险的 Token-2022 扩展
+    UnsupportedMintExtension = 22,
+    /// 池中尚无流动性，无法按池价格计算
+    EmptyPool = 23,
+    /// 闪电贷进行中，暂不允许该操作
+    FlashLoanActive = 24,
+    /// 闪电贷结束时金库余额未恢复到借出前余额加费用
+    FlashLoanNotRepaid = 25,
+    /// 路由中相邻两跳的代币不衔接，或同一个池出现多次
+    InvalidRoute = 26,
+    /// 传给回调程序的账户数量超过上限
+    TooManyAccounts = 27,
+}
+
+impl AmmError {
+    /// 返回错误对应的可读信息
+    pub const fn message(&self) -> &'static str {
+        match self {
+            AmmError::Expired => "Transaction expired",
+            AmmError::RentCalculationFailed => {
+                "Failed to calculate rent-exempt balance for the config"
+            }
+            AmmError::MintRentCalculationFailed => {
+                "Failed to calculate rent-exempt balance for the LP mint"
+            }
+            AmmError::SlippageExceeded => "Slippage tolerance exceeded",
+            AmmError::InvalidVault => "Vault is not the config's associated token account",
+            AmmError::InvalidVaultOwner => "Vault is not owned by the config",
+            AmmError::InvalidMint => "Token account mint does not match the pool",
+            AmmError::InvalidTokenAccountOwner => "Token account is not owned by the user",
+            AmmError::PoolDisabled => "Pool state does not allow this operation",
+            AmmError::ZeroAmount => "Amount must be greater than zero",
+            AmmError::CurveError => "Constant product curve calculation failed",
+            AmmError::InvalidMintLp => "LP mint is not the pool's LP mint",
+            AmmError::InvalidLockedLp => "Locked LP account is not the config's LP token account",
+            AmmError::IdenticalMints => "Mint X and mint Y must be different",
+            AmmError::InvalidFee => "Fee is out of range",
+            AmmError::InvalidState => "Invalid AMM state",
+            AmmError::InsufficientInitialLiquidity => {
+                "Initial LP amount must exceed the minimum liquidity"
+            }
+            AmmError::ConfigLocked => "Pool has no authority and its config is locked",
+            AmmError::Unauthorized => "Signer is not the pool authority",
+            AmmError::NoPendingAuthority => "No pending authority transfer",
+            AmmError::InvalidPda => "Account is not the canonical PDA",
+            AmmError::UnsortedMints => "Mint X must sort before mint Y",
+            AmmError::UnsupportedMintExtension => "Mint has an unsupported Token-2022 extension",
+            AmmError::EmptyPool => "Pool has no liquidity",
+            AmmError::FlashLoanActive => "A flash loan is in progress",
+            AmmError::FlashLoanNotRepaid => "Flash loan was not repaid with fee",
+            AmmError::InvalidRoute => "Swap route hops do not connect or repeat a pool",
+            AmmError::TooManyAccounts => "Too many accounts passed to the callback program",
+        }
+    }
+
+    /// 根据链上返回的错误码还原 AmmError
+    pub const fn from_code(code: u32) -> Option<Self> {
+        match code {
+            0 => Some(AmmError::Expired),
+            1 => Some(AmmError::RentCalculationFailed),
+            2 => Some(AmmError::MintRentCalculationFailed),
+            3 => Some(AmmError::SlippageExceeded),
+            4 => Some(AmmError::InvalidVault),
+            5 => Some(AmmError::InvalidVaultOwner),
+            6 => Some(AmmError::InvalidMint),
+            7 => Some(AmmError::InvalidTokenAccountOwner),
+            8 => Some(AmmError::PoolDisabled),
+            9 => Some(AmmError::ZeroAmount),
+            10 => Some(AmmError::CurveError),
+            11 => Some(AmmError::InvalidMintLp),
+            12 => Some(AmmError::InvalidLockedLp),
+            13 => Some(AmmError::IdenticalMints),
+            14 => Some(AmmError::InvalidFee),
+            15 => Some(AmmError::InvalidState),
+            16 => Some(AmmError::InsufficientInitialLiquidity),
+            17 => Some(AmmError::ConfigLocked),
+            18 => Some(AmmError::Unauthorized),
+            19 => Some(AmmError::NoPendingAuthority),
+            20 => Some(AmmError::InvalidPda),
+            21 => Some(AmmError::UnsortedMints),
+            22 => Some(AmmError::UnsupportedMintExtension),
+            23 => Some(AmmError::EmptyPool),
+            24 => Some(AmmError::FlashLoanActive),
+            25 => Some(AmmError::FlashLoanNotRepaid),
+            26 => Some(AmmError::InvalidRoute),
+            27 => Some(AmmError::TooManyAccounts),
+            _ => None,
+        }
+    }
+}
+
+impl From<AmmError> for ProgramError {
+    fn from(e: AmmError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl From<CurveError> for AmmError {
+    /// 曲线的滑点错误单独映射为 SlippageExceeded，其余统一为 CurveError
+    fn from(e: CurveError) -> Self {
+        match e {
+            CurveError::SlippageLimitExceeded => AmmError::SlippageExceeded,
+            _ => AmmError::CurveError,
+        }
+    }
+}
+
+/// ========== 错误码查询 ==========
+///
+/// 供客户端使用：将 `ProgramError::Custom(code)` 中的错误码转换为可读信息，
+/// 未知错误码返回 `None`。
+pub fn error_message(code: u32) -> Option<&'static str> {
+    AmmError::from_code(code).map(|e| e.message())
+}
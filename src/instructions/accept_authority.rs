@@ -0,0 +1,90 @@
+use crate::{error::AmmError, state::Config};
+
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// ========== 接受权限转移指令所需的账户 ==========
+///
+/// 新权限管理员确认接受池权限时所需的所有账户。
+pub struct AcceptAuthorityAccounts<'a> {
+    /// 待接受的新权限管理员（必须是签名者，且与 pending_authority 一致）
+    pub new_authority: &'a AccountView,
+    /// AMM 配置账户（将更新 authority）
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for AcceptAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取接受权限转移指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [new_authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 新权限管理员必须签名，以证明其确实掌握该地址的私钥
+        if !new_authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self {
+            new_authority,
+            config,
+        })
+    }
+}
+
+/// ========== AcceptAuthority 指令实现 ==========
+///
+/// 两步权限转移的第二步：pending_authority 签名后成为新的权限管理员。
+pub struct AcceptAuthority<'a> {
+    /// 所需的账户
+    pub accounts: AcceptAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for AcceptAuthority<'a> {
+    type Error = ProgramError;
+
+    /// 构建 AcceptAuthority 指令，验证签名者是待接受的新权限管理员
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        // 该指令不需要任何参数
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = AcceptAuthorityAccounts::try_from(accounts)?;
+
+        match Config::load(accounts.config)?.has_pending_authority() {
+            None => return Err(AmmError::NoPendingAuthority.into()),
+            Some(pending) => {
+                if pending != *accounts.new_authority.address() {
+                    return Err(AmmError::Unauthorized.into());
+                }
+            }
+        }
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptAuthority<'a> {
+    /// 该指令的鉴别器值（6 表示接受权限转移指令）
+    pub const DISCRIMINATOR: &'a u8 = &6;
+
+    /// 执行接受流程：更新 authority 并清空 pending_authority
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.set_authority(*self.accounts.new_authority.address());
+        config.set_pending_authority(Address::default());
+
+        Ok(())
+    }
+}
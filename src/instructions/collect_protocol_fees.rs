@@ -0,0 +1,179 @@
+use crate::{
+    error::AmmError,
+    pool::PoolAccounts,
+    state::Config,
+    token_interface::{
+        check_token_account, mint_unchecked, token_account_unchecked, TransferChecked,
+    },
+};
+
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, ProgramResult};
+
+/// ========== 提取协议费用指令所需的账户 ==========
+///
+/// 权限管理员将累计的协议费用从金库转入协议金库时所需的所有账户。
+pub struct CollectProtocolFeesAccounts<'a> {
+    /// 池的权限管理员（必须是签名者）
+    pub authority: &'a AccountView,
+    /// LP 代币的 mint 账户（仅用于验证池账户）
+    pub mint_lp: &'a AccountView,
+    /// 代币 X 的 mint 账户
+    pub mint_x: &'a AccountView,
+    /// 代币 Y 的 mint 账户
+    pub mint_y: &'a AccountView,
+    /// 代币 X 的金库账户（转出代币 X 协议费用）
+    pub vault_x: &'a AccountView,
+    /// 代币 Y 的金库账户（转出代币 Y 协议费用）
+    pub vault_y: &'a AccountView,
+    /// 协议金库的代币 X 账户（接收代币 X 协议费用）
+    pub treasury_x: &'a AccountView,
+    /// 协议金库的代币 Y 账户（接收代币 Y 协议费用）
+    pub treasury_y: &'a AccountView,
+    /// AMM 配置账户（记录累计的协议费用）
+    pub config: &'a AccountView,
+    /// 代币 X 所属的 Token 程序
+    pub token_program_x: &'a AccountView,
+    /// 代币 Y 所属的 Token 程序
+    pub token_program_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for CollectProtocolFeesAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取提取协议费用指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, mint_lp, mint_x, mint_y, vault_x, vault_y, treasury_x, treasury_y, config, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证权限管理员是交易签名者
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ 池账户验证 ============
+        // Config、LP mint、代币 mint、Token 程序和金库的验证与其他指令共用
+        PoolAccounts {
+            config,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+        }
+        .validate()?;
+
+        // ============ 协议金库账户验证 ============
+        // 协议金库可以归任何人所有，但持有的代币必须与池的 mint 对应
+        check_token_account(treasury_x, token_program_x.address())?;
+        check_token_account(treasury_y, token_program_y.address())?;
+        {
+            let treasury_x_account = unsafe { token_account_unchecked(treasury_x) };
+            let treasury_y_account = unsafe { token_account_unchecked(treasury_y) };
+            if treasury_x_account.mint() != mint_x.address()
+                || treasury_y_account.mint() != mint_y.address()
+            {
+                return Err(AmmError::InvalidMint.into());
+            }
+        }
+
+        Ok(Self {
+            authority,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            treasury_x,
+            treasury_y,
+            config,
+            token_program_x,
+            token_program_y,
+        })
+    }
+}
+
+/// ========== CollectProtocolFees 指令实现 ==========
+///
+/// 权限管理员将金库中累计的协议费用全部转入协议金库，并清零累计值。
+pub struct CollectProtocolFees<'a> {
+    /// 所需的账户
+    pub accounts: CollectProtocolFeesAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for CollectProtocolFees<'a> {
+    type Error = ProgramError;
+
+    /// 构建 CollectProtocolFees 指令，验证签名者是池的权限管理员
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        // 该指令不需要任何参数
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = CollectProtocolFeesAccounts::try_from(accounts)?;
+
+        Config::load(accounts.config)?.check_authority(accounts.authority.address())?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CollectProtocolFees<'a> {
+    /// 该指令的鉴别器值（8 表示提取协议费用指令）
+    pub const DISCRIMINATOR: &'a u8 = &8;
+
+    /// 执行提取流程
+    ///
+    /// 先清零 Config 中的累计值，再由 Config PDA 签名将费用从金库转出。
+    pub fn process(&mut self) -> ProgramResult {
+        // ============ 步骤1：读取并清零累计的协议费用 ============
+        let (owed_x, owed_y) = {
+            let mut config = Config::load_mut(self.accounts.config)?;
+            // 闪电贷期间金库余额暂时减少，不允许转出协议费用
+            config.check_no_flash_loan()?;
+            let owed = (config.protocol_fees_owed_x(), config.protocol_fees_owed_y());
+            config.set_protocol_fees_owed_x(0);
+            config.set_protocol_fees_owed_y(0);
+            owed
+        };
+
+        // ============ 步骤2：准备 Config PDA 签名 ============
+        let cfg = Config::load(self.accounts.config)?;
+        let config_seeds = cfg.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        // ============ 步骤3：从金库转出协议费用 ============
+        if owed_x > 0 {
+            TransferChecked {
+                from: self.accounts.vault_x,
+                mint: self.accounts.mint_x,
+                to: self.accounts.treasury_x,
+                authority: self.accounts.config,
+                amount: owed_x,
+                decimals: unsafe { mint_unchecked(self.accounts.mint_x) }.decimals(),
+                token_program: self.accounts.token_program_x.address(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        if owed_y > 0 {
+            TransferChecked {
+                from: self.accounts.vault_y,
+                mint: self.accounts.mint_y,
+                to: self.accounts.treasury_y,
+                authority: self.accounts.config,
+                amount: owed_y,
+                decimals: unsafe { mint_unchecked(self.accounts.mint_y) }.decimals(),
+                token_program: self.accounts.token_program_y.address(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        Ok(())
+    }
+}
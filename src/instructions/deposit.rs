@@ -1,13 +1,20 @@
-use crate::state::{AmmState, Config};
+use crate::{
+    error::AmmError,
+    pool::{DepositQuote, PoolAccounts},
+    return_data::LiquidityReturnData,
+    state::{Config, MINIMUM_LIQUIDITY},
+    token_interface::{check_token_account, mint_unchecked, TransferChecked},
+};
 use core::mem::size_of;
 
 use pinocchio::{
-    cpi::{Seed, Signer},
+    cpi::Signer,
     error::ProgramError,
     AccountView, Address, ProgramResult,
 };
 use pinocchio::sysvars::Sysvar;
-use pinocchio_token::instructions::{MintTo, Transfer};
+use pinocchio_token::instructions::MintTo;
+use pinocchio_token::state::TokenAccount;
 use pinocchio_token::ID as TOKEN_PROGRAM_ID;
 
 /// ========== 存入流动性指令所需的账户 ==========
@@ -16,21 +23,17 @@ use pinocchio_token::ID as TOKEN_PROGRAM_ID;
 pub struct DepositAccounts<'a> {
     /// 提供流动性的用户账户（必须是签名者）
     pub user: &'a AccountView,
-    /// LP 代币的 mint 账户（用于铸造 LP 代币给用户）
-    pub mint_lp: &'a AccountView,
-    /// 代币 X 的金库账户（接收用户的代币 X）
-    pub vault_x: &'a AccountView,
-    /// 代币 Y 的金库账户（接收用户的代币 Y）
-    pub vault_y: &'a AccountView,
+    /// 池账户（Config、LP mint、代币 mint、金库和 Token 程序）
+    pub pool: PoolAccounts<'a>,
     /// 用户的代币 X 关联代币账户（ATA）
     pub user_x_ata: &'a AccountView,
     /// 用户的代币 Y 关联代币账户（ATA）
     pub user_y_ata: &'a AccountView,
     /// 用户的 LP 代币关联代币账户（ATA）
     pub user_lp_ata: &'a AccountView,
-    /// AMM 配置账户（包含池参数）
-    pub config: &'a AccountView,
-    /// SPL Token 程序
+    /// Config PDA 的 LP 代币关联代币账户（首次存入时接收永久锁定的最小流动性）
+    pub locked_lp: &'a AccountView,
+    /// SPL Token 程序（LP mint 所属）
     pub token_program: &'a AccountView,
 }
 
@@ -39,7 +42,7 @@ impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
 
     /// 验证和提取存入指令所需的账户
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, mint_lp, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, config, token_program] =
+        let [user, mint_lp, mint_x, mint_y, vault_x, vault_y, user_x_ata, user_y_ata, user_lp_ata, locked_lp, config, token_program, token_program_x, token_program_y] =
             accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
@@ -50,78 +53,134 @@ impl<'a> TryFrom<&'a [AccountView]> for DepositAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // ============ Config 账户验证 ============
-        // 验证 Config 账户的大小和所有权
-        if config.data_len() != Config::LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !config.owned_by(&crate::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-
         // ============ Token Program 验证 ============
-        // 确保使用的是真实的 SPL Token 程序
+        // LP mint 始终属于 SPL Token 程序
         if token_program.address() != &TOKEN_PROGRAM_ID {
-            return Err(ProgramError::InvalidArgument);
+            return Err(ProgramError::IncorrectProgramId);
         }
 
-        // ============ 其他账户基本检查 ============
-        // 注：完整的验证（如 mint_lp 的 Mint 格式检查）可以在后续步骤中进行
-
-        // ============ 金库 PDA 验证 ============
-        // 从 Config 加载数据以验证金库地址是否匹配
-        let cfg = Config::load(config)?;
-
-        // 验证 vault_x 是否为正确的 PDA
-        let (derived_vault_x, _) = Address::find_program_address(
-            &[
-                config.address().as_ref(),
-                token_program.address().as_ref(),
-                cfg.mint_x().as_ref(),
-            ],
-            &pinocchio_associated_token_account::ID,
-        );
-        if derived_vault_x != *vault_x.address() {
-            return Err(ProgramError::InvalidAccountData);
+        // ============ 池账户验证 ============
+        // Config、LP mint、代币 mint、Token 程序和金库的验证与其他指令共用
+        let pool = PoolAccounts {
+            config,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+        };
+        pool.validate()?;
+
+        // ============ 代币账户格式和所有权验证 ============
+        // 验证用户的代币账户都由对应的 Token 程序拥有
+        check_token_account(user_x_ata, token_program_x.address())?;
+        check_token_account(user_y_ata, token_program_y.address())?;
+        if user_lp_ata.data_len() != TokenAccount::LEN
+            || !user_lp_ata.owned_by(token_program.address())
+        {
+            return Err(ProgramError::InvalidAccountOwner);
         }
 
-        // 验证 vault_y 是否为正确的 PDA
-        let (derived_vault_y, _) = Address::find_program_address(
+        // ============ 锁定 LP 账户验证 ============
+        // 必须是 Config PDA 自己的 LP ATA，确保锁定的最小流动性无人可动用
+        let (derived_locked_lp, _) = Address::find_program_address(
             &[
                 config.address().as_ref(),
                 token_program.address().as_ref(),
-                cfg.mint_y().as_ref(),
+                mint_lp.address().as_ref(),
             ],
             &pinocchio_associated_token_account::ID,
         );
-        if derived_vault_y != *vault_y.address() {
-            return Err(ProgramError::InvalidAccountData);
+        if derived_locked_lp != *locked_lp.address() {
+            return Err(AmmError::InvalidLockedLp.into());
         }
 
         Ok(Self {
             user,
-            mint_lp,
-            vault_x,
-            vault_y,
+            pool,
             user_x_ata,
             user_y_ata,
             user_lp_ata,
-            config,
+            locked_lp,
             token_program,
         })
     }
 }
 
+impl DepositAccounts<'_> {
+    /// ========== 执行存入报价 ==========
+    ///
+    /// 将报价中的代币 X、Y 从用户账户转入金库（用户签名），
+    /// 引导存入时先将 `MINIMUM_LIQUIDITY` 铸造到锁定账户，再向用户铸造 LP（Config PDA 签名），
+    /// 最后发布存入结果。Deposit 和 DepositByAmounts 共用。
+    pub fn execute(&self, quote: &DepositQuote) -> ProgramResult {
+        // 将用户的代币 X 从其 ATA 转入金库
+        TransferChecked {
+            from: self.user_x_ata,
+            mint: self.pool.mint_x,
+            to: self.pool.vault_x,
+            authority: self.user,
+            amount: quote.amount_x,
+            decimals: unsafe { mint_unchecked(self.pool.mint_x) }.decimals(),
+            token_program: self.pool.token_program_x.address(),
+        }
+        .invoke()?;
+
+        // 将用户的代币 Y 从其 ATA 转入金库
+        TransferChecked {
+            from: self.user_y_ata,
+            mint: self.pool.mint_y,
+            to: self.pool.vault_y,
+            authority: self.user,
+            amount: quote.amount_y,
+            decimals: unsafe { mint_unchecked(self.pool.mint_y) }.decimals(),
+            token_program: self.pool.token_program_y.address(),
+        }
+        .invoke()?;
+
+        // Config PDA 是 mint authority，需要其签名
+        let config = Config::load(self.pool.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        // 引导存入时先将最小流动性铸造到锁定账户
+        if quote.is_bootstrap {
+            MintTo {
+                mint: self.pool.mint_lp,
+                account: self.locked_lp,
+                mint_authority: self.pool.config,
+                amount: MINIMUM_LIQUIDITY,
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        MintTo {
+            mint: self.pool.mint_lp,
+            account: self.user_lp_ata,
+            mint_authority: self.pool.config,
+            amount: quote.amount_lp,
+        }
+        .invoke_signed(&signer)?;
+
+        // 发布存入结果
+        LiquidityReturnData::from(quote).set();
+
+        Ok(())
+    }
+}
+
 /// ========== 存入流动性指令的数据结构 ==========
 /// 
 /// 包含用户提供流动性时的所有参数。
 #[repr(C, packed)] 
 pub struct DepositInstructionData {
-    /// 用户想要铸造的 LP 代币数量
+    /// 用户想要铸造的 LP 代币数量（引导存入时为可接受的最少 LP 总量）
     pub amount: u64,
-    /// 愿意支付的最大代币 X 数量（滑点保护）
+    /// 愿意从用户账户转出的最大代币 X 数量（滑点保护，含 Token-2022 转账手续费）
     pub max_x: u64,
-    /// 愿意支付的最大代币 Y 数量（滑点保护）
+    /// 愿意从用户账户转出的最大代币 Y 数量（滑点保护，含 Token-2022 转账手续费）
     pub max_y: u64,
     /// 交易过期时间（Unix 时间戳，0 表示不限制）
     pub expiration: i64,
@@ -142,14 +201,14 @@ impl<'a> TryFrom<&'a [u8]> for DepositInstructionData {
 
         // 验证 LP 数量大于 0
         if instruction_data.amount == 0 {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::ZeroAmount.into());
         }
 
         // ============ 过期时间检查 ==========
         // 如果设置了过期时间，验证当前时间未超过
         let clock = pinocchio::sysvars::clock::Clock::get()?;
         if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
-            return Err(ProgramError::Custom(0));
+            return Err(AmmError::Expired.into());
         }
 
         Ok(instruction_data)
@@ -176,11 +235,12 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Deposit<'a> {
         
         // ============ Config 状态验证 ============
         // 验证 AMM 是否已初始化且处于可用状态
-        let config = Config::load_mut(accounts.config)?;
+        let config = Config::load(accounts.pool.config)?;
 
-        if config.state() != (AmmState::Initialized as u8) {
-            return Err(ProgramError::InvalidAccountData);
+        if !config.amm_state()?.allows_deposit() {
+            return Err(AmmError::PoolDisabled.into());
         }
+        drop(config);
 
         Ok(Self {
             accounts,
@@ -195,60 +255,29 @@ impl<'a> Deposit<'a> {
 
     /// 执行存入流程
     /// 
-    /// 将用户的代币转入金库，并铸造对应的 LP 代币给用户。
+    /// 根据当前金库余额和 LP 供应量，按比例计算铸造 `amount` 个 LP 代币所需的代币 X 和 Y
+    /// （向上取整，任一侧为 0 时交易失败），只从用户账户转入这部分数量，并铸造 LP 代币给用户。
+    /// `max_x` / `max_y` 作为滑点上限，超过则交易失败。
+    ///
+    /// 首次存入（引导存入）时，`max_x` / `max_y` 全部存入，铸造的 LP 总量为金库收到数量的
+    /// 几何平均数且不少于 `amount`，其中 `MINIMUM_LIQUIDITY` 个 LP 被永久锁定在
+    /// Config PDA 的 LP 账户中，用户获得其余部分。
+    ///
+    /// 若代币带有 Token-2022 转账手续费，转出数量会按手续费向上补足，
+    /// 确保金库实际收到曲线要求的数量，`max_x` / `max_y` 约束的是含手续费的转出数量。
     pub fn process(&mut self) -> ProgramResult {
-        // ============ 步骤1：转移代币 X ==========
-        // 将用户的代币 X 从其 ATA 转入金库
-        if self.instruction_data.max_x > 0 {
-            Transfer {
-                from: self.accounts.user_x_ata,
-                to: self.accounts.vault_x,
-                authority: self.accounts.user,
-                amount: self.instruction_data.max_x,
-            }
-            .invoke()?;
-        }
-
-        // ============ 步骤2：转移代币 Y ==========
-        // 将用户的代币 Y 从其 ATA 转入金库
-        if self.instruction_data.max_y > 0 {
-            Transfer {
-                from: self.accounts.user_y_ata,
-                to: self.accounts.vault_y,
-                authority: self.accounts.user,
-                amount: self.instruction_data.max_y,
-            }
-            .invoke()?;
-        }
-
-        // ============ 步骤3：铸造 LP 代币 ==========
-        // 向用户铸造 LP 代币作为流动性凭证
-        // Config PDA 是 mint authority，需要其签名
-        if self.instruction_data.amount > 0 {
-            let config = Config::load(self.accounts.config)?;
-            let seed_bytes = config.seed().to_le_bytes();
-            let bump = config.config_bump();
-
-            // 准备 Config PDA 的签名种子
-            let config_seeds = [
-                Seed::from(b"config"),
-                Seed::from(&seed_bytes),
-                Seed::from(config.mint_x().as_ref()),
-                Seed::from(config.mint_y().as_ref()),
-                Seed::from(&bump),
-            ];
-
-            let signer = [Signer::from(&config_seeds)];
-
-            MintTo {
-                mint: self.accounts.mint_lp,
-                account: self.accounts.user_lp_ata,
-                mint_authority: self.accounts.config,
-                amount: self.instruction_data.amount,
-            }
-            .invoke_signed(&signer)?;
-        }
+        // ============ 步骤1：计算所需的代币数量并检查滑点 ==========
+        // 与 Quote 指令共用同一计算路径
+        let quote = {
+            let cfg = Config::load(self.accounts.pool.config)?;
+            unsafe { self.accounts.pool.pool(&cfg)? }.quote_deposit(
+                self.instruction_data.amount,
+                self.instruction_data.max_x,
+                self.instruction_data.max_y,
+            )?
+        };
 
-        Ok(())
+        // ============ 步骤2：转入代币并铸造 LP ==========
+        self.accounts.execute(&quote)
     }
 }
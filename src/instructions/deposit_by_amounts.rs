@@ -0,0 +1,114 @@
+use crate::{error::AmmError, instructions::deposit::DepositAccounts, state::Config};
+use core::mem::size_of;
+
+use pinocchio::{
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+
+/// ========== 按代币数量存入指令的数据结构 ==========
+///
+/// 用户给出愿意存入的代币 X 和 Y 数量，以及愿意接受的最少 LP 数量。
+/// 账户列表与 Deposit 指令完全相同。
+#[repr(C, packed)]
+pub struct DepositByAmountsInstructionData {
+    /// 愿意从用户账户转出的最大代币 X 数量（含 Token-2022 转账手续费）
+    pub amount_x: u64,
+    /// 愿意从用户账户转出的最大代币 Y 数量（含 Token-2022 转账手续费）
+    pub amount_y: u64,
+    /// 用户愿意接受的最少 LP 数量（滑点保护）
+    pub min_lp: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositByAmountsInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析按代币数量存入指令参数，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        const DEPOSIT_BY_AMOUNTS_DATA_LEN: usize = size_of::<u64>() * 3 + size_of::<i64>();
+        if data.len() != DEPOSIT_BY_AMOUNTS_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // 验证两侧代币数量都大于 0
+        if instruction_data.amount_x == 0 || instruction_data.amount_y == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== DepositByAmounts 指令实现 ==========
+///
+/// Deposit 的按代币数量模式：用户无需根据可能已过时的储备预先计算 LP 数量，
+/// 程序按当前储备取 `amount_x` / `amount_y` 中符合比例的最大部分存入，
+/// 铸造对应的 LP，多余的代币留在用户账户中。
+pub struct DepositByAmounts<'a> {
+    /// 所需的账户（与 Deposit 相同）
+    pub accounts: DepositAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: DepositByAmountsInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for DepositByAmounts<'a> {
+    type Error = ProgramError;
+
+    /// 构建 DepositByAmounts 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositByAmountsInstructionData::try_from(data)?;
+
+        // ============ Config 状态验证 ============
+        // 验证 AMM 是否已初始化且处于可用状态
+        let config = Config::load(accounts.pool.config)?;
+        if !config.amm_state()?.allows_deposit() {
+            return Err(AmmError::PoolDisabled.into());
+        }
+        drop(config);
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositByAmounts<'a> {
+    /// 该指令的鉴别器值（13 表示按代币数量存入指令）
+    pub const DISCRIMINATOR: &'a u8 = &13;
+
+    /// 执行按代币数量存入流程
+    ///
+    /// 引导存入时两侧数量全部存入并确定初始价格，LP 数量为金库收到数量的几何平均数，
+    /// 其中 `MINIMUM_LIQUIDITY` 个 LP 被永久锁定（见 [`crate::pool::Pool::quote_deposit_by_amounts`]）。
+    pub fn process(&mut self) -> ProgramResult {
+        // ============ 步骤1：计算存入数量和铸造的 LP ==========
+        // 与 Deposit 及 Quote 指令共用同一计算路径
+        let quote = {
+            let cfg = Config::load(self.accounts.pool.config)?;
+            unsafe { self.accounts.pool.pool(&cfg)? }.quote_deposit_by_amounts(
+                self.instruction_data.amount_x,
+                self.instruction_data.amount_y,
+                self.instruction_data.min_lp,
+            )?
+        };
+
+        // ============ 步骤2：转入代币并铸造 LP ==========
+        // 只转入符合比例的部分，多余的代币留在用户账户
+        self.accounts.execute(&quote)
+    }
+}
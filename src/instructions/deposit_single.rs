@@ -0,0 +1,173 @@
+use crate::{
+    error::AmmError,
+    instructions::deposit::DepositAccounts,
+    return_data::LiquidityReturnData,
+    state::Config,
+    token_interface::{mint_unchecked, TransferChecked},
+};
+use core::mem::size_of;
+
+use pinocchio::{
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+use pinocchio_token::instructions::MintTo;
+
+/// ========== 单边存入指令的数据结构 ==========
+///
+/// 用户只提供一侧代币，指定愿意接受的最少 LP 数量。
+/// 账户列表与 Deposit 指令完全相同，另一侧的用户代币账户不会被使用。
+#[repr(C, packed)]
+pub struct DepositSingleInstructionData {
+    /// 标志位：true 表示存入代币 X，false 表示存入代币 Y
+    pub is_x: bool,
+    /// 从用户账户转出的输入代币数量（含 Token-2022 转账手续费）
+    pub amount_in: u64,
+    /// 用户愿意接受的最少 LP 数量（滑点保护）
+    pub min_lp: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for DepositSingleInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析单边存入指令参数，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        const DEPOSIT_SINGLE_DATA_LEN: usize =
+            size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        if data.len() != DEPOSIT_SINGLE_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        // ============ 金额有效性检查 ==========
+        // 验证输入数量大于 0
+        if instruction_data.amount_in == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== DepositSingle 指令实现 ==========
+///
+/// 用户只用一侧代币提供流动性（zap in）：部分输入按池价格和交换费用隐含地换成另一侧代币，
+/// 再按交换后的储备比例铸造 LP。换出的代币从未离开金库，整个过程在同一笔指令内原子完成。
+pub struct DepositSingle<'a> {
+    /// 所需的账户（与 Deposit 相同）
+    pub accounts: DepositAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: DepositSingleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for DepositSingle<'a> {
+    type Error = ProgramError;
+
+    /// 构建 DepositSingle 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = DepositAccounts::try_from(accounts)?;
+        let instruction_data = DepositSingleInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> DepositSingle<'a> {
+    /// 该指令的鉴别器值（11 表示单边存入指令）
+    pub const DISCRIMINATOR: &'a u8 = &11;
+
+    /// 执行单边存入流程
+    ///
+    /// 隐含交换与 Swap 使用同一曲线和费率，交换费用中的协议分成同样计入待提取的协议费用。
+    /// 若输入代币带有 Token-2022 转账手续费，按金库实际收到的数量计算，
+    /// `amount_in` 为用户实际转出的数量（见 [`crate::pool::Pool::quote_deposit_single`]）。
+    pub fn process(&mut self) -> ProgramResult {
+        // ============ 步骤1：加载配置并计算铸造的 LP ============
+        // 可变借用仅在计算和记账期间持有，转账前释放
+        let quote = {
+            let mut cfg = Config::load_mut(self.accounts.pool.config)?;
+
+            // 单边存入包含隐含交换，池状态需同时允许存入和交换
+            let state = cfg.amm_state()?;
+            if !(state.allows_deposit() && state.allows_swap()) {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // 与 Quote 指令共用同一计算路径
+            let pool = unsafe { self.accounts.pool.pool(&cfg)? };
+            let quote = pool.quote_deposit_single(
+                self.instruction_data.is_x,
+                self.instruction_data.amount_in,
+                self.instruction_data.min_lp,
+            )?;
+
+            // 按协议分成比例从隐含交换的费用（以输入代币计）中累计协议费用
+            cfg.accrue_protocol_fee(self.instruction_data.is_x, quote.protocol_fee)?;
+
+            quote
+        };
+
+        // ============ 步骤2：转入输入代币 ============
+        // 只有输入侧代币从用户账户转入金库
+        let (from, mint, to, token_program) = match self.instruction_data.is_x {
+            true => (
+                self.accounts.user_x_ata,
+                self.accounts.pool.mint_x,
+                self.accounts.pool.vault_x,
+                self.accounts.pool.token_program_x,
+            ),
+            false => (
+                self.accounts.user_y_ata,
+                self.accounts.pool.mint_y,
+                self.accounts.pool.vault_y,
+                self.accounts.pool.token_program_y,
+            ),
+        };
+
+        TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount_in,
+            decimals: unsafe { mint_unchecked(mint) }.decimals(),
+            token_program: token_program.address(),
+        }
+        .invoke()?;
+
+        // ============ 步骤3：铸造 LP 代币 ==========
+        // Config PDA 是 mint authority，需要其签名
+        let config = Config::load(self.accounts.pool.config)?;
+        let config_seeds = config.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        MintTo {
+            mint: self.accounts.pool.mint_lp,
+            account: self.accounts.user_lp_ata,
+            mint_authority: self.accounts.pool.config,
+            amount: quote.deposit.amount_lp,
+        }
+        .invoke_signed(&signer)?;
+
+        // ============ 步骤4：发布存入结果 ==========
+        LiquidityReturnData::from(&quote).set();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,353 @@
+use crate::{
+    error::AmmError,
+    state::Config,
+    token_interface::{
+        check_token_account, mint_unchecked, token_account_unchecked, TransferChecked,
+    },
+};
+use core::{
+    mem::{size_of, MaybeUninit},
+    slice,
+};
+
+use pinocchio::{
+    cpi::{slice_invoke, Signer},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+    AccountView, Address, ProgramResult,
+};
+
+/// ========== 闪电贷指令所需的账户 ==========
+///
+/// 借款人从金库借出一种代币、在同一指令内由接收程序归还时所需的所有账户。
+/// 固定账户之后的剩余账户原样传给接收程序。
+pub struct FlashLoanAccounts<'a> {
+    /// 借款人（必须是签名者，签名权限会传递给接收程序）
+    pub borrower: &'a AccountView,
+    /// 借出代币的 mint 账户
+    pub mint: &'a AccountView,
+    /// 借出代币的金库账户
+    pub vault: &'a AccountView,
+    /// 接收借款的代币账户
+    pub borrower_ata: &'a AccountView,
+    /// AMM 配置账户（记录重入保护标志和闪电贷费用）
+    pub config: &'a AccountView,
+    /// 借出代币所属的 Token 程序
+    pub token_program: &'a AccountView,
+    /// 借款人指定的接收程序（在其中使用并归还借款）
+    pub receiver_program: &'a AccountView,
+    /// 传给接收程序的账户
+    pub receiver_accounts: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FlashLoanAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取闪电贷指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [borrower, mint, vault, borrower_ata, config, token_program, receiver_program, receiver_accounts @ ..] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证借款人是交易签名者
+        if !borrower.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        // ============ 接收程序验证 ============
+        check_receiver_program(receiver_program)?;
+
+        Ok(Self {
+            borrower,
+            mint,
+            vault,
+            borrower_ata,
+            config,
+            token_program,
+            receiver_program,
+            receiver_accounts,
+        })
+    }
+}
+
+impl FlashLoanAccounts<'_> {
+    /// 验证 mint、Token 程序和金库属于该 Config 的 X 或 Y 一侧
+    fn check_side(&self, config: &Config, is_x: bool) -> Result<(), ProgramError> {
+        let (mint, token_program) = match is_x {
+            true => (config.mint_x(), config.token_program_x()),
+            false => (config.mint_y(), config.token_program_y()),
+        };
+
+        // ============ 代币 mint 验证 ============
+        if self.mint.address() != mint {
+            return Err(AmmError::InvalidMint.into());
+        }
+
+        // ============ Token 程序验证 ============
+        // 必须与初始化时记录在 Config 中的 Token 程序一致
+        if self.token_program.address() != token_program {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // ============ 金库 PDA 验证 ============
+        // 金库是 Config 持有的关联代币账户
+        let (derived_vault, _) = Address::find_program_address(
+            &[
+                self.config.address().as_ref(),
+                token_program.as_ref(),
+                mint.as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        if derived_vault != *self.vault.address() {
+            return Err(AmmError::InvalidVault.into());
+        }
+
+        // 归还检查需要读取金库余额，因此金库必须是格式正确的代币账户
+        check_token_account(self.vault, token_program)
+    }
+
+    /// 读取金库当前余额
+    fn vault_amount(&self) -> u64 {
+        unsafe { token_account_unchecked(self.vault) }.amount()
+    }
+}
+
+/// ========== 闪电贷指令的数据结构 ==========
+///
+/// 固定字段之后的全部数据原样作为接收程序的指令数据。
+#[repr(C, packed)]
+pub struct FlashLoanInstructionData {
+    /// 标志位：true 表示借出代币 X，false 表示借出代币 Y
+    pub is_x: bool,
+    /// 借出数量（从金库转出的数量）
+    pub amount: u64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FlashLoanInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析闪电贷指令的固定字段，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        if data.len() != FlashLoan::DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // 验证借出数量大于 0
+        if instruction_data.amount == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== FlashLoan 指令实现 ==========
+///
+/// 从金库借出代币，调用借款人指定的接收程序，并在接收程序返回后验证金库余额
+/// 不少于借出前余额加闪电贷费用，否则整笔交易失败。
+///
+/// 借出期间 Config 中的重入保护标志被置位，交换、存入、提取和提取协议费用都会被拒绝。
+/// 交易失败时标志随账户修改一起回滚，不会残留。
+pub struct FlashLoan<'a> {
+    /// 所需的账户
+    pub accounts: FlashLoanAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: FlashLoanInstructionData,
+    /// 传给接收程序的指令数据
+    pub receiver_data: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for FlashLoan<'a> {
+    type Error = ProgramError;
+
+    /// 构建 FlashLoan 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FlashLoanAccounts::try_from(accounts)?;
+        if data.len() < Self::DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (data, receiver_data) = data.split_at(Self::DATA_LEN);
+        let instruction_data = FlashLoanInstructionData::try_from(data)?;
+
+        // 验证借出代币属于本池
+        let config = Config::load(accounts.config)?;
+        accounts.check_side(&config, instruction_data.is_x)?;
+        drop(config);
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            receiver_data,
+        })
+    }
+}
+
+impl<'a> FlashLoan<'a> {
+    /// 该指令的鉴别器值（14 表示闪电贷指令）
+    pub const DISCRIMINATOR: &'a u8 = &14;
+
+    /// 固定字段的字节长度
+    pub const DATA_LEN: usize = size_of::<bool>() + size_of::<u64>();
+
+    /// 计算借出 `amount` 需要支付的闪电贷费用（向上取整）
+    #[inline(always)]
+    pub fn fee(amount: u64, flash_fee: u16) -> Result<u64, ProgramError> {
+        let fee = (amount as u128 * flash_fee as u128).div_ceil(10_000);
+        u64::try_from(fee).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// 执行闪电贷流程
+    ///
+    /// 若代币带有 Token-2022 转账手续费，借款人实际收到的数量少于 `amount`，
+    /// 归还时仍需使金库实际收到借出数量加闪电贷费用，两次转账的手续费均由借款人承担。
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+        let amount = self.instruction_data.amount;
+
+        // ============ 步骤1：置位重入保护并记录借出前余额 ============
+        // 可变借用仅在记账期间持有，CPI 前释放
+        let (balance_before, fee) = {
+            let mut cfg = Config::load_mut(self.accounts.config)?;
+
+            // 验证 AMM 已初始化且处于可用状态
+            if !cfg.amm_state()?.allows_swap() {
+                return Err(AmmError::PoolDisabled.into());
+            }
+            // 不允许嵌套闪电贷
+            cfg.check_no_flash_loan()?;
+            cfg.set_flash_loan_active(true);
+
+            (
+                self.accounts.vault_amount(),
+                Self::fee(amount, cfg.flash_fee())?,
+            )
+        };
+
+        // ============ 步骤2：从金库转出借款 ============
+        // Config PDA 是金库的权限方，需要其签名
+        {
+            let cfg = Config::load(self.accounts.config)?;
+            let config_seeds = cfg.signer_seeds();
+            let signer = [Signer::from(&config_seeds)];
+
+            TransferChecked {
+                from: self.accounts.vault,
+                mint: self.accounts.mint,
+                to: self.accounts.borrower_ata,
+                authority: self.accounts.config,
+                amount,
+                decimals: unsafe { mint_unchecked(self.accounts.mint) }.decimals(),
+                token_program: self.accounts.token_program.address(),
+            }
+            .invoke_signed(&signer)?;
+        }
+
+        // ============ 步骤3：调用接收程序 ============
+        invoke_receiver(
+            self.accounts.receiver_program,
+            self.accounts.receiver_accounts,
+            self.receiver_data,
+        )?;
+
+        // ============ 步骤4：验证归还并清除重入保护 ============
+        let required = balance_before
+            .checked_add(fee)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if self.accounts.vault_amount() < required {
+            return Err(AmmError::FlashLoanNotRepaid.into());
+        }
+
+        let mut cfg = Config::load_mut(self.accounts.config)?;
+        cfg.set_flash_loan_active(false);
+
+        // 闪电贷费用留在金库中归 LP，按协议分成比例累计协议费用
+        let protocol_fee = (fee as u128 * cfg.protocol_fee_share() as u128 / 10_000) as u64;
+        cfg.accrue_protocol_fee(is_x, protocol_fee)?;
+
+        Ok(())
+    }
+}
+
+/// ========== 接收程序验证 ==========
+///
+/// 接收程序必须是可执行程序，且不能回调本程序。
+pub fn check_receiver_program(receiver_program: &AccountView) -> ProgramResult {
+    if !receiver_program.executable() || receiver_program.address() == &crate::ID {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// 传给接收程序的最大账户数量（账户元数据放在栈上的定长数组中）
+pub const MAX_RECEIVER_ACCOUNTS: usize = 64;
+
+/// ========== 调用接收程序 ==========
+///
+/// 将剩余账户（保持各自的可写和签名标志）和指令数据原样传给接收程序。
+/// 不使用 Config PDA 签名，接收程序只能获得调用者自身的签名权限。
+/// 账户超过 [`MAX_RECEIVER_ACCOUNTS`] 个时返回 TooManyAccounts。
+/// FlashLoan 和 FlashSwap 共用。
+pub fn invoke_receiver(
+    receiver_program: &AccountView,
+    accounts: &[AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    if accounts.len() > MAX_RECEIVER_ACCOUNTS {
+        return Err(AmmError::TooManyAccounts.into());
+    }
+
+    let mut instruction_accounts =
+        [const { MaybeUninit::<InstructionAccount>::uninit() }; MAX_RECEIVER_ACCOUNTS];
+    let mut account_views =
+        [const { MaybeUninit::<&AccountView>::uninit() }; MAX_RECEIVER_ACCOUNTS];
+    for ((instruction_account, account_view), account) in instruction_accounts
+        .iter_mut()
+        .zip(account_views.iter_mut())
+        .zip(accounts)
+    {
+        instruction_account.write(InstructionAccount::new(
+            account.address(),
+            account.is_writable(),
+            account.is_signer(),
+        ));
+        account_view.write(account);
+    }
+
+    // 前 accounts.len() 个元素已在上面的循环中初始化
+    let (instruction_accounts, account_views) = unsafe {
+        (
+            slice::from_raw_parts(
+                instruction_accounts.as_ptr() as *const InstructionAccount,
+                accounts.len(),
+            ),
+            slice::from_raw_parts(
+                account_views.as_ptr() as *const &AccountView,
+                accounts.len(),
+            ),
+        )
+    };
+
+    slice_invoke(
+        &InstructionView {
+            program_id: receiver_program.address(),
+            accounts: instruction_accounts,
+            data,
+        },
+        account_views,
+    )
+}
@@ -0,0 +1,221 @@
+use crate::{
+    error::AmmError,
+    instructions::{
+        flash_loan::{check_receiver_program, invoke_receiver},
+        swap::SwapAccounts,
+    },
+    pool::check_flash_swap,
+    state::Config,
+    token_interface::token_account_unchecked,
+};
+use core::mem::size_of;
+
+use pinocchio::{
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+
+/// ========== 闪电交换指令所需的账户 ==========
+///
+/// 前 [`SwapAccounts::LEN`] 个账户与 Swap 指令相同（不支持可选接收账户，输出转入用户自己的代币账户），
+/// 随后是回调程序，其余账户原样传给回调程序。
+pub struct FlashSwapAccounts<'a> {
+    /// 与 Swap 相同的账户
+    pub swap: SwapAccounts<'a>,
+    /// 调用者指定的回调程序（在其中使用输出并支付输入）
+    pub callback_program: &'a AccountView,
+    /// 传给回调程序的账户
+    pub callback_accounts: &'a [AccountView],
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for FlashSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取闪电交换指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        if accounts.len() < SwapAccounts::LEN {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let (swap_accounts, [callback_program, callback_accounts @ ..]) =
+            accounts.split_at(SwapAccounts::LEN)
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let swap = SwapAccounts::try_from(swap_accounts)?;
+
+        // ============ 回调程序验证 ============
+        check_receiver_program(callback_program)?;
+
+        Ok(Self {
+            swap,
+            callback_program,
+            callback_accounts,
+        })
+    }
+}
+
+/// ========== 闪电交换指令的数据结构 ==========
+///
+/// 固定字段之后的全部数据原样作为回调程序的指令数据。
+#[repr(C, packed)]
+pub struct FlashSwapInstructionData {
+    /// 标志位：true 表示先取得 Y、最后支付 X，false 表示先取得 X、最后支付 Y
+    pub is_x: bool,
+    /// 金库先行转出的输出代币数量
+    pub amount_out: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for FlashSwapInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析闪电交换指令的固定字段，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        if data.len() != FlashSwap::DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        // 验证输出数量大于 0
+        if instruction_data.amount_out == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== FlashSwap 指令实现 ==========
+///
+/// Uniswap V2 风格的闪电交换：金库先把输出代币转给用户，再调用回调程序，
+/// 回调程序使用输出后把输入代币直接转入金库。回调返回后按金库余额验证
+/// 扣除交换费用后的常数乘积不变量（见 [`check_flash_swap`]），不满足则整笔交易失败。
+///
+/// 回调期间复用闪电贷的重入保护标志，交换、存入、提取和闪电贷都会被拒绝。
+pub struct FlashSwap<'a> {
+    /// 所需的账户
+    pub accounts: FlashSwapAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: FlashSwapInstructionData,
+    /// 传给回调程序的指令数据
+    pub callback_data: &'a [u8],
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for FlashSwap<'a> {
+    type Error = ProgramError;
+
+    /// 构建 FlashSwap 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = FlashSwapAccounts::try_from(accounts)?;
+        if data.len() < Self::DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (data, callback_data) = data.split_at(Self::DATA_LEN);
+        let instruction_data = FlashSwapInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+            callback_data,
+        })
+    }
+}
+
+impl<'a> FlashSwap<'a> {
+    /// 该指令的鉴别器值（15 表示闪电交换指令）
+    pub const DISCRIMINATOR: &'a u8 = &15;
+
+    /// 固定字段的字节长度
+    pub const DATA_LEN: usize = size_of::<bool>() + size_of::<u64>() + size_of::<i64>();
+
+    /// 执行闪电交换流程
+    ///
+    /// 输入可以用任意一侧代币支付（归还部分输出同样需要支付交换费用），
+    /// 两侧交换费用中的协议分成都计入待提取的协议费用。
+    /// 转入数量按金库余额计算，Token-2022 转账手续费由回调程序自行补足。
+    pub fn process(&mut self) -> ProgramResult {
+        let is_x = self.instruction_data.is_x;
+        let amount_out = self.instruction_data.amount_out;
+        let (out_x, out_y) = match is_x {
+            true => (0, amount_out),
+            false => (amount_out, 0),
+        };
+
+        // ============ 步骤1：记录交换前储备并置位重入保护 ============
+        // 可变借用仅在记账期间持有，CPI 前释放
+        let pool = {
+            let mut cfg = Config::load_mut(self.accounts.swap.config)?;
+
+            // 验证 AMM 已初始化且处于可用状态
+            if !cfg.amm_state()?.allows_swap() {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // 读取净储备（闪电贷进行中时返回 FlashLoanActive）
+            let pool = unsafe { self.accounts.swap.pool(&cfg)? };
+
+            // 输出数量必须小于池中该侧储备
+            let reserve_out = match is_x {
+                true => pool.reserve_y,
+                false => pool.reserve_x,
+            };
+            if amount_out >= reserve_out {
+                return Err(AmmError::CurveError.into());
+            }
+
+            cfg.set_flash_loan_active(true);
+
+            pool
+        };
+
+        // ============ 步骤2：先行转出输出代币 ============
+        // 与 Swap 共用 Config PDA 签名的转出逻辑
+        self.accounts.swap.transfer_out(is_x, amount_out)?;
+
+        // ============ 步骤3：调用回调程序 ============
+        invoke_receiver(
+            self.accounts.callback_program,
+            self.accounts.callback_accounts,
+            self.callback_data,
+        )?;
+
+        // ============ 步骤4：验证不变量并清除重入保护 ============
+        let mut cfg = Config::load_mut(self.accounts.swap.config)?;
+        let (new_reserve_x, new_reserve_y) = cfg.reserves(
+            unsafe { token_account_unchecked(self.accounts.swap.vault_x) }.amount(),
+            unsafe { token_account_unchecked(self.accounts.swap.vault_y) }.amount(),
+        )?;
+
+        let (fee_x, fee_y) = check_flash_swap(
+            pool.reserve_x,
+            pool.reserve_y,
+            out_x,
+            out_y,
+            new_reserve_x,
+            new_reserve_y,
+            pool.fee,
+        )?;
+
+        cfg.set_flash_loan_active(false);
+
+        // 按协议分成比例从两侧交换费用中累计协议费用
+        let protocol_fee =
+            |fee: u64| (fee as u128 * pool.protocol_fee_share as u128 / 10_000) as u64;
+        cfg.accrue_protocol_fee(true, protocol_fee(fee_x))?;
+        cfg.accrue_protocol_fee(false, protocol_fee(fee_y))?;
+
+        Ok(())
+    }
+}
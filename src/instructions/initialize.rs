@@ -1,4 +1,8 @@
-use crate::state::Config;
+use crate::{
+    error::AmmError,
+    state::Config,
+    token_interface::{check_mint_account, check_mint_extensions, is_token_program, mint_unchecked},
+};
 use core::mem::size_of;
 use core::mem::MaybeUninit;
 use pinocchio::{
@@ -6,6 +10,7 @@ use pinocchio::{
     error::ProgramError,
     AccountView, Address, ProgramResult,
 };
+use pinocchio_associated_token_account::instructions::CreateIdempotent;
 use pinocchio_system::instructions::CreateAccount;
 use pinocchio_system::ID as SYSTEM_PROGRAM_ID;
 use pinocchio_token::instructions::InitializeMint2;
@@ -21,10 +26,26 @@ pub struct InitializeAccounts<'a> {
     pub mint_lp: &'a AccountView,
     /// AMM 配置账户（PDA，存储所有池参数）
     pub config: &'a AccountView,
+    /// 代币 X 的 mint 账户
+    pub mint_x: &'a AccountView,
+    /// 代币 Y 的 mint 账户
+    pub mint_y: &'a AccountView,
+    /// 代币 X 的金库账户（Config 的关联代币账户，创建时为空）
+    pub vault_x: &'a AccountView,
+    /// 代币 Y 的金库账户（Config 的关联代币账户，创建时为空）
+    pub vault_y: &'a AccountView,
+    /// Config 的 LP 关联代币账户（用于锁定最小流动性，创建时为空）
+    pub locked_lp: &'a AccountView,
     /// Solana 系统程序（用于创建账户）
     pub system_program: &'a AccountView,
-    /// SPL Token 程序（用于初始化 mint 和 token 账户）
+    /// SPL Token 程序（用于初始化 LP mint 和锁定 LP 账户）
     pub token_program: &'a AccountView,
+    /// 代币 X 所属的 Token 程序（SPL Token 或 Token-2022）
+    pub token_program_x: &'a AccountView,
+    /// 代币 Y 所属的 Token 程序（SPL Token 或 Token-2022）
+    pub token_program_y: &'a AccountView,
+    /// 关联代币账户程序（用于创建金库）
+    pub associated_token_program: &'a AccountView,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
@@ -32,7 +53,9 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
 
     /// 从原始账户数组构建 InitializeAccounts，进行所有验证
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [initializer, mint_lp, config, system_program, token_program] = accounts else {
+        let [initializer, mint_lp, config, mint_x, mint_y, vault_x, vault_y, locked_lp, system_program, token_program, token_program_x, token_program_y, associated_token_program] =
+            accounts
+        else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
 
@@ -45,21 +68,42 @@ impl<'a> TryFrom<&'a [AccountView]> for InitializeAccounts<'a> {
         // ============ System Program 验证 ============
         // 验证提供的是真实的 Solana 系统程序
         if system_program.address() != &SYSTEM_PROGRAM_ID {
-            return Err(ProgramError::InvalidArgument);
+            return Err(ProgramError::IncorrectProgramId);
         }
 
         // ============ Token Program 验证 ============
         // 验证提供的是真实的 SPL Token 程序
         if token_program.address() != &TOKEN_PROGRAM_ID {
-            return Err(ProgramError::InvalidArgument);
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // 代币 X / Y 可以属于 SPL Token 或 Token-2022
+        if !is_token_program(token_program_x.address())
+            || !is_token_program(token_program_y.address())
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // ============ Associated Token Program 验证 ============
+        // 验证提供的是真实的关联代币账户程序
+        if associated_token_program.address() != &pinocchio_associated_token_account::ID {
+            return Err(ProgramError::IncorrectProgramId);
         }
 
         Ok(Self {
             initializer,
             mint_lp,
             config,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            locked_lp,
             system_program,
             token_program,
+            token_program_x,
+            token_program_y,
+            associated_token_program,
         })
     }
 }
@@ -73,43 +117,60 @@ pub struct InitializeInstructionData {
     pub seed: u64,
     /// 交换费用（bps，范围 0-9999）
     pub fee: u16,
-    /// 代币 X 的 mint 地址（32 字节）
+    /// 代币 X 的 mint 地址（32 字节，按字节序必须小于 mint_y）
     pub mint_x: [u8; 32],
     /// 代币 Y 的 mint 地址（32 字节）
     pub mint_y: [u8; 32],
-    /// 配置 PDA 的 bump seed
+    /// 配置 PDA 的 bump seed（必须是 canonical bump）
     pub config_bump: [u8; 1],
-    /// LP mint PDA 的 bump seed
+    /// LP mint PDA 的 bump seed（必须是 canonical bump）
     pub lp_bump: [u8; 1],
     /// 权限管理员地址（可选，如果不提供则为零地址）
     pub authority: [u8; 32],
+    /// LP mint 的精度（可选，必须随 authority 一起提供；
+    /// 不提供时为 [`Self::LP_DECIMALS_FROM_MINTS`]，取代币 X / Y 中较大的精度）。
+    /// 仅影响钱包和浏览器中 LP 数量的显示，LP 的铸造和销毁数量按原始单位计算，与精度无关；
+    /// 取值范围与 Token 程序的 `decimals` 一致，不另设上限
+    pub lp_decimals: u8,
+}
+
+impl InitializeInstructionData {
+    /// lp_decimals 的默认值标记：由代币对的 mint 精度推导
+    pub const LP_DECIMALS_FROM_MINTS: u8 = u8::MAX;
 }
 
 impl TryFrom<&[u8]> for InitializeInstructionData {
     type Error = ProgramError;
 
-    /// 从字节数组解析初始化数据，支持带或不带 authority 的格式
+    /// 从字节数组解析初始化数据，支持带或不带 authority / lp_decimals 的格式
     fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
         // 计算不同格式的数据长度
-        const INITIALIZE_DATA_LEN_WITH_AUTHORITY: usize = size_of::<InitializeInstructionData>();
+        const INITIALIZE_DATA_LEN_FULL: usize = size_of::<InitializeInstructionData>();
+        const INITIALIZE_DATA_LEN_WITH_AUTHORITY: usize =
+            INITIALIZE_DATA_LEN_FULL - size_of::<u8>();
         const INITIALIZE_DATA_LEN: usize =
             INITIALIZE_DATA_LEN_WITH_AUTHORITY - size_of::<[u8; 32]>();
 
         let instruction_data = match data.len() {
-            // 完整格式：包含 authority 字段
-            INITIALIZE_DATA_LEN_WITH_AUTHORITY => unsafe {
-                (data.as_ptr() as *const Self).read_unaligned()
-            },
-            // 简化格式：不包含 authority，需要补充零字节
-            INITIALIZE_DATA_LEN => {
-                let mut raw: MaybeUninit<[u8; INITIALIZE_DATA_LEN_WITH_AUTHORITY]> =
-                    MaybeUninit::uninit();
+            // 完整格式：包含 authority 和 lp_decimals 字段
+            INITIALIZE_DATA_LEN_FULL => unsafe { (data.as_ptr() as *const Self).read_unaligned() },
+            // 简化格式：缺少 authority 和 / 或 lp_decimals，需要补充默认值
+            INITIALIZE_DATA_LEN | INITIALIZE_DATA_LEN_WITH_AUTHORITY => {
+                let mut raw: MaybeUninit<[u8; INITIALIZE_DATA_LEN_FULL]> = MaybeUninit::uninit();
                 let raw_ptr = raw.as_mut_ptr() as *mut u8;
                 unsafe {
                     // 复制已提供的数据
-                    core::ptr::copy_nonoverlapping(data.as_ptr(), raw_ptr, INITIALIZE_DATA_LEN);
-                    // 将 authority 字段填充为零（表示无特定权限管理员）
-                    core::ptr::write_bytes(raw_ptr.add(INITIALIZE_DATA_LEN), 0, 32);
+                    core::ptr::copy_nonoverlapping(data.as_ptr(), raw_ptr, data.len());
+                    // 缺少的 authority 字段填充为零（表示无特定权限管理员）
+                    core::ptr::write_bytes(
+                        raw_ptr.add(data.len()),
+                        0,
+                        INITIALIZE_DATA_LEN_WITH_AUTHORITY - data.len(),
+                    );
+                    // lp_decimals 使用默认值标记，由代币对的 mint 精度推导
+                    raw_ptr
+                        .add(INITIALIZE_DATA_LEN_WITH_AUTHORITY)
+                        .write(Self::LP_DECIMALS_FROM_MINTS);
                     // 转换为目标结构体
                     (raw.as_ptr() as *const Self).read_unaligned()
                 }
@@ -142,7 +203,54 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Initialize<'a> {
 
         // 验证 mint_x 和 mint_y 是不同的代币
         if instruction_data.mint_x == instruction_data.mint_y {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::IdenticalMints.into());
+        }
+
+        // 验证代币对按规范顺序排列（mint_x < mint_y），
+        // 避免 (A,B) 与 (B,A) 在同一 seed 下各自成池、分散流动性
+        if instruction_data.mint_x > instruction_data.mint_y {
+            return Err(AmmError::UnsortedMints.into());
+        }
+
+        // 验证传入的 mint 账户与指令数据一致
+        if accounts.mint_x.address().as_ref() != instruction_data.mint_x
+            || accounts.mint_y.address().as_ref() != instruction_data.mint_y
+        {
+            return Err(AmmError::InvalidMint.into());
+        }
+
+        // 验证 mint_x 和 mint_y 是由对应 Token 程序拥有的已初始化 mint 账户，
+        // 并拒绝带有不支持或危险扩展的 Token-2022 mint
+        for (mint, token_program) in [
+            (accounts.mint_x, accounts.token_program_x),
+            (accounts.mint_y, accounts.token_program_y),
+        ] {
+            check_mint_account(mint, token_program.address())?;
+            if !unsafe { mint_unchecked(mint) }.is_initialized() {
+                return Err(AmmError::InvalidMint.into());
+            }
+            check_mint_extensions(mint)?;
+        }
+
+        // ============ Config PDA 验证 ============
+        // 重新推导 canonical PDA，拒绝调用者提供的非 canonical bump，
+        // 保证同一组 seed / mint 只能对应唯一的 Config
+        let (config_address, config_bump) = Config::find_address(
+            instruction_data.seed,
+            Address::new_from_array(instruction_data.mint_x),
+            Address::new_from_array(instruction_data.mint_y),
+        );
+        if config_address != *accounts.config.address()
+            || [config_bump] != instruction_data.config_bump
+        {
+            return Err(AmmError::InvalidPda.into());
+        }
+
+        // ============ LP Mint PDA 验证 ============
+        let (mint_lp_address, lp_bump) =
+            Config::find_mint_lp_address(accounts.config.address());
+        if mint_lp_address != *accounts.mint_lp.address() || [lp_bump] != instruction_data.lp_bump {
+            return Err(AmmError::InvalidPda.into());
         }
 
         Ok(Self {
@@ -161,6 +269,7 @@ impl<'a> Initialize<'a> {
     /// 整个流程包括：
     /// 1. 创建配置 PDA 账户并初始化
     /// 2. 创建 LP mint PDA 账户并初始化
+    /// 3. 创建 Config 持有的代币 X / Y 金库和锁定 LP 账户
     pub fn process(&mut self) -> ProgramResult {
         use pinocchio::sysvars::Sysvar;
 
@@ -179,12 +288,22 @@ impl<'a> Initialize<'a> {
         let mint_y = Address::new_from_array(self.instruction_data.mint_y);
         let authority = Address::new_from_array(self.instruction_data.authority);
 
+        // 未指定 LP 精度时取代币 X / Y 中较大的精度（LP 精度仅用于显示）
+        let lp_decimals = match self.instruction_data.lp_decimals {
+            InitializeInstructionData::LP_DECIMALS_FROM_MINTS => {
+                let decimals_x = unsafe { mint_unchecked(self.accounts.mint_x) }.decimals();
+                let decimals_y = unsafe { mint_unchecked(self.accounts.mint_y) }.decimals();
+                decimals_x.max(decimals_y)
+            }
+            lp_decimals => lp_decimals,
+        };
+
         // ============ 第1步：创建 Config PDA 账户 ============
         // 获取当前租期信息以计算创建账户所需的 lamports
         let rent = pinocchio::sysvars::rent::Rent::get()?;
         let config_lamports = rent
             .try_minimum_balance(Config::LEN)
-            .map_err(|_| ProgramError::Custom(1))?;
+            .map_err(|_| AmmError::RentCalculationFailed)?;
         
         // 创建 Config 账户，使用生成的 PDA 进行签名
         let cfsigner = [Signer::from(&config_seeds)];
@@ -206,8 +325,12 @@ impl<'a> Initialize<'a> {
                 authority,
                 mint_x,
                 mint_y,
+                *self.accounts.token_program_x.address(),
+                *self.accounts.token_program_y.address(),
                 self.instruction_data.fee,
                 self.instruction_data.config_bump,
+                self.instruction_data.lp_bump,
+                lp_decimals,
             )?;
         }
 
@@ -221,7 +344,7 @@ impl<'a> Initialize<'a> {
         // 计算 Mint 账户所需的 lamports（SPL Mint 固定大小为 82 字节）
         let mint_lamports = rent
             .try_minimum_balance(82)
-            .map_err(|_| ProgramError::Custom(2))?;
+            .map_err(|_| AmmError::MintRentCalculationFailed)?;
 
         CreateAccount {
             from: self.accounts.initializer,
@@ -235,12 +358,48 @@ impl<'a> Initialize<'a> {
         // ============ 第4步：初始化 LP Mint 数据 ============
         InitializeMint2 {
             mint: self.accounts.mint_lp,
-            decimals: 6,
+            decimals: lp_decimals,
             mint_authority: self.accounts.config.address(),
             freeze_authority: None,
         }
         .invoke()?;
 
+        // ============ 第5步：创建代币 X / Y 金库 ============
+        // 金库是 Config PDA 的关联代币账户，由关联代币账户程序创建。
+        // Config 地址可以提前推导，任何人都能抢先创建这些关联代币账户，
+        // 因此使用幂等创建：已存在时由关联代币账户程序验证其 mint、所有者和 Token 程序后直接复用
+        CreateIdempotent {
+            funding_account: self.accounts.initializer,
+            account: self.accounts.vault_x,
+            wallet: self.accounts.config,
+            mint: self.accounts.mint_x,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program_x,
+        }
+        .invoke()?;
+
+        CreateIdempotent {
+            funding_account: self.accounts.initializer,
+            account: self.accounts.vault_y,
+            wallet: self.accounts.config,
+            mint: self.accounts.mint_y,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program_y,
+        }
+        .invoke()?;
+
+        // ============ 第6步：创建锁定 LP 账户 ============
+        // 首次存入时 MINIMUM_LIQUIDITY 会被铸造到此账户
+        CreateIdempotent {
+            funding_account: self.accounts.initializer,
+            account: self.accounts.locked_lp,
+            wallet: self.accounts.config,
+            mint: self.accounts.mint_lp,
+            system_program: self.accounts.system_program,
+            token_program: self.accounts.token_program,
+        }
+        .invoke()?;
+
         Ok(())
     }
 }
@@ -3,17 +3,56 @@
 /// 本模块组织和导出所有 AMM 智能合约支持的指令实现。
 /// 每个指令在单独的文件中定义，本模块负责协调它们。
 
+/// 新权限管理员接受池权限的指令实现
+pub mod accept_authority;
+/// 提取协议费用的指令实现
+pub mod collect_protocol_fees;
 /// 提取流动性的指令实现
 pub mod deposit;
+/// 按代币数量存入流动性的指令实现
+pub mod deposit_by_amounts;
+/// 单边存入流动性的指令实现
+pub mod deposit_single;
+/// 从金库借出代币的闪电贷指令实现
+pub mod flash_loan;
+/// 先转出输出、回调后收取输入的闪电交换指令实现
+pub mod flash_swap;
 /// 初始化新的 AMM 流动性池
 pub mod initialize;
+/// 提议转移池权限的指令实现
+pub mod propose_authority;
+/// 只读报价的指令实现
+pub mod quote;
+/// 永久放弃池权限的指令实现
+pub mod renounce_authority;
+/// 经过多个池的路由交换指令实现
+pub mod route_swap;
 /// 代币交换的指令实现
 pub mod swap;
+/// 精确输出代币交换的指令实现
+pub mod swap_exact_out;
+/// 权限管理员更新池配置的指令实现
+pub mod update_config;
 /// 提取流动性的指令实现
 pub mod withdraw;
+/// 单边提取流动性的指令实现
+pub mod withdraw_single;
 
 // 将所有指令导出到顶层，为外部模块和指令分发提供便利
+pub use accept_authority::*;
+pub use collect_protocol_fees::*;
 pub use deposit::*;
+pub use deposit_by_amounts::*;
+pub use deposit_single::*;
+pub use flash_loan::*;
+pub use flash_swap::*;
 pub use initialize::*;
+pub use propose_authority::*;
+pub use quote::*;
+pub use renounce_authority::*;
+pub use route_swap::*;
 pub use swap::*;
-pub use withdraw::*;
\ No newline at end of file
+pub use swap_exact_out::*;
+pub use update_config::*;
+pub use withdraw::*;
+pub use withdraw_single::*;
\ No newline at end of file
@@ -0,0 +1,103 @@
+use crate::state::Config;
+use core::mem::size_of;
+
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// ========== 提议权限转移指令所需的账户 ==========
+///
+/// 当前权限管理员提议将池权限转移给新地址时所需的所有账户。
+pub struct ProposeAuthorityAccounts<'a> {
+    /// 当前的权限管理员（必须是签名者）
+    pub authority: &'a AccountView,
+    /// AMM 配置账户（记录待接受的新权限管理员）
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for ProposeAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取提议权限转移指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证权限管理员是交易签名者
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { authority, config })
+    }
+}
+
+/// ========== 提议权限转移指令的数据结构 ==========
+#[repr(C, packed)]
+pub struct ProposeAuthorityInstructionData {
+    /// 新的权限管理员地址（全 0 表示取消当前待处理的转移）
+    pub new_authority: [u8; 32],
+}
+
+impl<'a> TryFrom<&'a [u8]> for ProposeAuthorityInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析提议权限转移指令参数
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        if data.len() != size_of::<[u8; 32]>() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        Ok(unsafe { (data.as_ptr() as *const Self).read_unaligned() })
+    }
+}
+
+/// ========== ProposeAuthority 指令实现 ==========
+///
+/// 两步权限转移的第一步：当前权限管理员记录一个待接受的新地址。
+/// 新地址必须通过 AcceptAuthority 签名确认后才真正生效，
+/// 因此填错地址不会导致池失去管理员，可以重新提议或取消。
+pub struct ProposeAuthority<'a> {
+    /// 所需的账户
+    pub accounts: ProposeAuthorityAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: ProposeAuthorityInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for ProposeAuthority<'a> {
+    type Error = ProgramError;
+
+    /// 构建 ProposeAuthority 指令，验证签名者是当前权限管理员
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = ProposeAuthorityAccounts::try_from(accounts)?;
+        let instruction_data = ProposeAuthorityInstructionData::try_from(data)?;
+
+        Config::load(accounts.config)?.check_authority(accounts.authority.address())?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ProposeAuthority<'a> {
+    /// 该指令的鉴别器值（5 表示提议权限转移指令）
+    pub const DISCRIMINATOR: &'a u8 = &5;
+
+    /// 执行提议流程，将新地址写入 pending_authority
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+        config.set_pending_authority(Address::new_from_array(self.instruction_data.new_authority));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,187 @@
+use crate::{
+    error::AmmError,
+    instructions::{
+        DepositByAmountsInstructionData, DepositInstructionData, DepositSingleInstructionData,
+        SwapExactOutInstructionData, SwapInstructionData, WithdrawInstructionData,
+        WithdrawSingleInstructionData,
+    },
+    pool::PoolAccounts,
+    return_data::{LiquidityReturnData, SwapQuoteReturnData},
+    state::Config,
+};
+
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+/// ========== 报价指令所需的账户 ==========
+///
+/// 计算报价只需要读取池状态，所有账户均为只读，不需要签名者。
+/// 账户顺序与 [`PoolAccounts`] 相同，验证也与各指令共用。
+pub struct QuoteAccounts<'a> {
+    /// 被报价的池的账户
+    pub pool: PoolAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for QuoteAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取报价指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let pool = PoolAccounts::try_from(accounts)?;
+
+        Ok(Self { pool })
+    }
+}
+
+/// ========== 报价指令的数据结构 ==========
+///
+/// 第一个字节选择报价的操作，其余数据与对应指令的数据完全相同：
+///
+/// | 变体 | 操作 | 数据布局 |
+/// |------|------|------|
+/// | 0 | Swap | [`SwapInstructionData`] |
+/// | 1 | SwapExactOut | [`SwapExactOutInstructionData`] |
+/// | 2 | Deposit | [`DepositInstructionData`] |
+/// | 3 | Withdraw | [`WithdrawInstructionData`] |
+/// | 4 | DepositSingle | [`DepositSingleInstructionData`] |
+/// | 5 | WithdrawSingle | [`WithdrawSingleInstructionData`] |
+/// | 6 | DepositByAmounts | [`DepositByAmountsInstructionData`] |
+pub enum QuoteInstructionData {
+    /// 精确输入交换
+    Swap(SwapInstructionData),
+    /// 精确输出交换
+    SwapExactOut(SwapExactOutInstructionData),
+    /// 存入流动性
+    Deposit(DepositInstructionData),
+    /// 提取流动性
+    Withdraw(WithdrawInstructionData),
+    /// 单边存入流动性
+    DepositSingle(DepositSingleInstructionData),
+    /// 单边提取流动性
+    WithdrawSingle(WithdrawSingleInstructionData),
+    /// 按代币数量存入流动性
+    DepositByAmounts(DepositByAmountsInstructionData),
+}
+
+impl<'a> TryFrom<&'a [u8]> for QuoteInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析报价指令参数，复用各指令自身的有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        match data.split_first() {
+            Some((0, data)) => Ok(Self::Swap(SwapInstructionData::try_from(data)?)),
+            Some((1, data)) => Ok(Self::SwapExactOut(SwapExactOutInstructionData::try_from(
+                data,
+            )?)),
+            Some((2, data)) => Ok(Self::Deposit(DepositInstructionData::try_from(data)?)),
+            Some((3, data)) => Ok(Self::Withdraw(WithdrawInstructionData::try_from(data)?)),
+            Some((4, data)) => Ok(Self::DepositSingle(DepositSingleInstructionData::try_from(
+                data,
+            )?)),
+            Some((5, data)) => Ok(Self::WithdrawSingle(
+                WithdrawSingleInstructionData::try_from(data)?,
+            )),
+            Some((6, data)) => Ok(Self::DepositByAmounts(
+                DepositByAmountsInstructionData::try_from(data)?,
+            )),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
+}
+
+/// ========== Quote 指令实现 ==========
+///
+/// 只读地计算交换、存入或提取的结果，不移动任何代币，结果通过返回数据发布。
+/// 前端可以在本地模拟该指令，而无需重新实现曲线和手续费取整。
+/// 各变体与对应的指令共用 [`PoolAccounts`] 的账户验证和 [`crate::pool::Pool`] 的同一条计算路径，
+/// 池状态不允许对应操作时同样返回 PoolDisabled。
+pub struct Quote<'a> {
+    /// 所需的账户
+    pub accounts: QuoteAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: QuoteInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Quote<'a> {
+    type Error = ProgramError;
+
+    /// 构建 Quote 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = QuoteAccounts::try_from(accounts)?;
+        let instruction_data = QuoteInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> Quote<'a> {
+    /// 该指令的鉴别器值（10 表示报价指令）
+    pub const DISCRIMINATOR: &'a u8 = &10;
+
+    /// 执行报价流程
+    ///
+    /// 交换变体发布 [`SwapQuoteReturnData`]，存入 / 提取类变体发布 [`LiquidityReturnData`]，
+    /// 与对应指令执行成功后发布的结果一致。
+    pub fn process(&mut self) -> ProgramResult {
+        let cfg = Config::load(self.accounts.pool.config)?;
+        let state = cfg.amm_state()?;
+        let pool = unsafe { self.accounts.pool.pool(&cfg)? };
+
+        match &self.instruction_data {
+            QuoteInstructionData::Swap(data) => {
+                if !state.allows_swap() {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_swap(data.is_x, data.amount, data.min)?;
+                SwapQuoteReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::SwapExactOut(data) => {
+                if !state.allows_swap() {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_swap_exact_out(data.is_x, data.amount_out, data.max_in)?;
+                SwapQuoteReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::Deposit(data) => {
+                if !state.allows_deposit() {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_deposit(data.amount, data.max_x, data.max_y)?;
+                LiquidityReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::Withdraw(data) => {
+                if !state.allows_withdraw() {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_withdraw(data.amount, data.min_x, data.min_y)?;
+                LiquidityReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::DepositSingle(data) => {
+                if !(state.allows_deposit() && state.allows_swap()) {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_deposit_single(data.is_x, data.amount_in, data.min_lp)?;
+                LiquidityReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::WithdrawSingle(data) => {
+                if !(state.allows_withdraw() && state.allows_swap()) {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote = pool.quote_withdraw_single(data.is_x, data.amount, data.min_out)?;
+                LiquidityReturnData::from(&quote).set();
+            }
+            QuoteInstructionData::DepositByAmounts(data) => {
+                if !state.allows_deposit() {
+                    return Err(AmmError::PoolDisabled.into());
+                }
+                let quote =
+                    pool.quote_deposit_by_amounts(data.amount_x, data.amount_y, data.min_lp)?;
+                LiquidityReturnData::from(&quote).set();
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,93 @@
+use crate::state::Config;
+
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// ========== 放弃权限指令所需的账户 ==========
+///
+/// 权限管理员永久放弃池权限时所需的所有账户。
+pub struct RenounceAuthorityAccounts<'a> {
+    /// 当前的权限管理员（必须是签名者）
+    pub authority: &'a AccountView,
+    /// AMM 配置账户（将清空 authority）
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RenounceAuthorityAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取放弃权限指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证权限管理员是交易签名者
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { authority, config })
+    }
+}
+
+/// ========== RenounceAuthority 指令实现 ==========
+///
+/// 将 authority 和 pending_authority 清零，此后 `Config::has_authority()`
+/// 永远返回 None，池配置被永久锁定。该操作不可撤销。
+///
+/// 放弃后无人能再提取协议费用，因此协议分成比例同时清零，
+/// 尚未提取的协议费用释放回储备归 LP 所有。
+pub struct RenounceAuthority<'a> {
+    /// 所需的账户
+    pub accounts: RenounceAuthorityAccounts<'a>,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for RenounceAuthority<'a> {
+    type Error = ProgramError;
+
+    /// 构建 RenounceAuthority 指令，验证签名者是当前权限管理员
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        // 该指令不需要任何参数
+        if !data.is_empty() {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let accounts = RenounceAuthorityAccounts::try_from(accounts)?;
+
+        Config::load(accounts.config)?.check_authority(accounts.authority.address())?;
+
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> RenounceAuthority<'a> {
+    /// 该指令的鉴别器值（7 表示放弃权限指令）
+    pub const DISCRIMINATOR: &'a u8 = &7;
+
+    /// 执行放弃流程，释放协议费用并清空 authority 与 pending_authority
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        // 闪电交换回调后按净储备验证不变量，回调期间不能改动协议费用记账
+        config.check_no_flash_loan()?;
+
+        // 停止累计协议费用，已累计的部分不再从储备中扣除
+        config.set_protocol_fee_share(0)?;
+        config.set_protocol_fees_owed_x(0);
+        config.set_protocol_fees_owed_y(0);
+
+        config.set_authority(Address::default());
+        config.set_pending_authority(Address::default());
+
+        Ok(())
+    }
+}
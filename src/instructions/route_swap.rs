@@ -0,0 +1,331 @@
+use crate::{
+    error::AmmError,
+    pool::{PoolAccounts, SwapQuote},
+    return_data::SwapReturnData,
+    state::Config,
+    token_interface::{
+        check_token_account, mint_unchecked, token_account_unchecked, TransferChecked,
+    },
+};
+use core::mem::size_of;
+
+use pinocchio::{
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+use pinocchio_token::state::TokenAccount;
+
+/// ========== 路由中的一跳 ==========
+#[derive(Clone, Copy)]
+pub struct RouteHop<'a> {
+    /// 该跳所用池的账户
+    pub pool: PoolAccounts<'a>,
+    /// 标志位：true 表示该跳用 X 换 Y，false 表示用 Y 换 X（由相邻两跳的 mint 推导）
+    pub is_x: bool,
+}
+
+impl<'a> RouteHop<'a> {
+    /// 该跳的（输入金库，输入 mint，输入侧 Token 程序）
+    #[inline(always)]
+    fn input(&self) -> (&'a AccountView, &'a AccountView, &'a AccountView) {
+        match self.is_x {
+            true => (
+                self.pool.vault_x,
+                self.pool.mint_x,
+                self.pool.token_program_x,
+            ),
+            false => (
+                self.pool.vault_y,
+                self.pool.mint_y,
+                self.pool.token_program_y,
+            ),
+        }
+    }
+
+    /// 该跳的（输出 mint，输出侧 Token 程序）
+    #[inline(always)]
+    fn output(&self) -> (&'a AccountView, &'a AccountView) {
+        match self.is_x {
+            true => (self.pool.mint_y, self.pool.token_program_y),
+            false => (self.pool.mint_x, self.pool.token_program_x),
+        }
+    }
+}
+
+/// ========== 路由交换指令所需的账户 ==========
+///
+/// 前三个账户是用户及其输入、输出代币账户，之后每 [`PoolAccounts::LEN`] 个账户描述一跳，
+/// 顺序为 config、mint_lp、mint_x、mint_y、vault_x、vault_y、token_program_x、token_program_y。
+/// 每一跳的交换方向由上一跳的输出 mint（第一跳为用户输入代币账户的 mint）推导。
+pub struct RouteSwapAccounts<'a> {
+    /// 执行交换的用户账户（必须是签名者）
+    pub user: &'a AccountView,
+    /// 用户的输入代币账户（第一跳的输入）
+    pub user_in_ata: &'a AccountView,
+    /// 用户的输出代币账户（最后一跳的输出）
+    pub user_out_ata: &'a AccountView,
+    /// 按顺序排列的各跳（前 `hop_count` 个为 `Some`）
+    pub hops: [Option<RouteHop<'a>>; RouteSwap::MAX_HOPS],
+    /// 跳数
+    pub hop_count: usize,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for RouteSwapAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取路由交换指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [user, user_in_ata, user_out_ata, pool_accounts @ ..] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证用户是交易签名者
+        if !user.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ 跳数验证 ============
+        // 剩余账户必须恰好是 1 到 MAX_HOPS 组池账户
+        if pool_accounts.is_empty() || pool_accounts.len() % PoolAccounts::LEN != 0 {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+        let hop_count = pool_accounts.len() / PoolAccounts::LEN;
+        if hop_count > RouteSwap::MAX_HOPS {
+            return Err(AmmError::InvalidRoute.into());
+        }
+
+        // ============ 用户输入代币 mint ============
+        // 第一跳的方向由输入 mint 推导，所有权和格式在确定输入侧 Token 程序后验证
+        if user_in_ata.data_len() < TokenAccount::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut mint_in = *unsafe { token_account_unchecked(user_in_ata) }.mint();
+
+        // ============ 各跳池账户验证和交换方向推导 ============
+        // 同一个池不能出现两次（各跳的报价都基于交换前的储备），
+        // 每一跳的输入 mint 必须是上一跳的输出 mint
+        let mut hops: [Option<RouteHop<'a>>; RouteSwap::MAX_HOPS] = [None; RouteSwap::MAX_HOPS];
+        for (i, chunk) in pool_accounts.chunks_exact(PoolAccounts::LEN).enumerate() {
+            let pool = PoolAccounts::try_from(chunk)?;
+            if hops[..i]
+                .iter()
+                .flatten()
+                .any(|other| other.pool.config.address() == pool.config.address())
+            {
+                return Err(AmmError::InvalidRoute.into());
+            }
+
+            let is_x = if pool.mint_x.address() == &mint_in {
+                true
+            } else if pool.mint_y.address() == &mint_in {
+                false
+            } else {
+                return Err(AmmError::InvalidRoute.into());
+            };
+
+            let hop = RouteHop { pool, is_x };
+            mint_in = *hop.output().0.address();
+            hops[i] = Some(hop);
+        }
+        let (Some(first), Some(last)) = (hops[0], hops[hop_count - 1]) else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // ============ 用户输入代币账户验证 ============
+        // 必须由第一跳输入侧的 Token 程序拥有，且归签名用户所有
+        let (_, _, token_program_in) = first.input();
+        check_token_account(user_in_ata, token_program_in.address())?;
+        if unsafe { token_account_unchecked(user_in_ata) }.owner() != user.address() {
+            return Err(AmmError::InvalidTokenAccountOwner.into());
+        }
+
+        // ============ 用户输出代币账户验证 ============
+        // 必须持有最后一跳的输出代币，且归签名用户所有
+        let (mint_out, token_program_out) = last.output();
+        check_token_account(user_out_ata, token_program_out.address())?;
+        {
+            let user_out_account = unsafe { token_account_unchecked(user_out_ata) };
+            if user_out_account.mint() != mint_out.address() {
+                return Err(AmmError::InvalidMint.into());
+            }
+            if user_out_account.owner() != user.address() {
+                return Err(AmmError::InvalidTokenAccountOwner.into());
+            }
+        }
+
+        Ok(Self {
+            user,
+            user_in_ata,
+            user_out_ata,
+            hops,
+            hop_count,
+        })
+    }
+}
+
+impl<'a> RouteSwapAccounts<'a> {
+    /// 第 `i` 跳（超出跳数时返回 `None`）
+    #[inline(always)]
+    pub fn hop(&self, i: usize) -> Option<&RouteHop<'a>> {
+        self.hops.get(i)?.as_ref()
+    }
+
+    /// 按顺序遍历各跳
+    #[inline(always)]
+    pub fn hops(&self) -> impl Iterator<Item = &RouteHop<'a>> {
+        self.hops[..self.hop_count].iter().flatten()
+    }
+}
+
+/// ========== 路由交换指令的数据结构 ==========
+///
+/// 整条路由只有一个输入数量、一个最终滑点保护和一个过期时间。
+#[repr(C, packed)]
+pub struct RouteSwapInstructionData {
+    /// 从用户输入代币账户转出的数量（含 Token-2022 转账手续费）
+    pub amount_in: u64,
+    /// 用户输出代币账户实际到账的最少数量（滑点保护，已扣除 Token-2022 转账手续费）
+    pub min_out: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for RouteSwapInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析路由交换指令参数，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        const ROUTE_SWAP_DATA_LEN: usize = size_of::<u64>() * 2 + size_of::<i64>();
+        if data.len() != ROUTE_SWAP_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        // ============ 金额有效性检查 ==========
+        // 验证输入数量和最小输出数量大于 0
+        if instruction_data.amount_in == 0 || instruction_data.min_out == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== RouteSwap 指令实现 ==========
+///
+/// 在一条指令内依次经过多个池完成交换（例如经 A/B 和 B/C 两个池用 A 换 C）。
+/// 每一跳与 Swap 使用同一曲线和费率，中间数量直接从上一跳的金库转入下一跳的金库，
+/// 用户无需持有中间代币账户，只在最后一跳检查一次滑点。
+pub struct RouteSwap<'a> {
+    /// 所需的账户
+    pub accounts: RouteSwapAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: RouteSwapInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for RouteSwap<'a> {
+    type Error = ProgramError;
+
+    /// 构建 RouteSwap 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = RouteSwapAccounts::try_from(accounts)?;
+        let instruction_data = RouteSwapInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> RouteSwap<'a> {
+    /// 该指令的鉴别器值（16 表示路由交换指令）
+    pub const DISCRIMINATOR: &'a u8 = &16;
+
+    /// 一条路由最多经过的池数量
+    pub const MAX_HOPS: usize = 4;
+
+    /// 执行路由交换流程
+    ///
+    /// 所有跳先按交换前的储备依次计算报价（各跳的池互不相同，计算互不影响），再统一转账。
+    /// 中间代币只从金库转出一次，若带有 Token-2022 转账手续费，下一跳按实际到账的数量计算。
+    /// 返回数据的输入、输出为整条路由的数量，费用和储备为最后一跳的结果。
+    pub fn process(&mut self) -> ProgramResult {
+        let accounts = &self.accounts;
+        let hop_count = accounts.hop_count;
+
+        // ============ 步骤1：依次计算各跳的交换结果 ============
+        // 与 Swap 共用同一计算路径，可变借用仅在计算和记账期间持有
+        let mut amount_in = self.instruction_data.amount_in;
+        let mut quotes: [Option<SwapQuote>; Self::MAX_HOPS] = [None; Self::MAX_HOPS];
+        for (i, hop) in accounts.hops().enumerate() {
+            let mut cfg = Config::load_mut(hop.pool.config)?;
+
+            // 验证 AMM 已初始化且处于可用状态
+            if !cfg.amm_state()?.allows_swap() {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // 只在最后一跳检查滑点
+            let min_out = match i + 1 == hop_count {
+                true => self.instruction_data.min_out,
+                false => 0,
+            };
+            let pool = unsafe { hop.pool.pool(&cfg)? };
+            let quote = pool.quote_swap(hop.is_x, amount_in, min_out)?;
+
+            // 按协议分成比例从交换费用（以该跳输入代币计）中累计协议费用
+            cfg.accrue_protocol_fee(hop.is_x, quote.protocol_fee)?;
+
+            // 下一跳的输入是本跳金库转出的数量
+            amount_in = quote.vault_out;
+            quotes[i] = Some(quote);
+        }
+
+        // ============ 步骤2：将用户的输入代币转入第一跳的金库 ============
+        let (Some(first), Some(last)) = (accounts.hop(0), quotes[hop_count - 1]) else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+        let (vault_in, mint_in, token_program_in) = first.input();
+        TransferChecked {
+            from: accounts.user_in_ata,
+            mint: mint_in,
+            to: vault_in,
+            authority: accounts.user,
+            amount: self.instruction_data.amount_in,
+            decimals: unsafe { mint_unchecked(mint_in) }.decimals(),
+            token_program: token_program_in.address(),
+        }
+        .invoke()?;
+
+        // ============ 步骤3：逐跳转出输出代币 ============
+        // 中间跳直接转入下一跳的金库，最后一跳转入用户的输出代币账户（各自 Config PDA 签名）
+        for (i, (hop, quote)) in accounts.hops().zip(quotes.iter().flatten()).enumerate() {
+            let to = match accounts.hop(i + 1) {
+                Some(next) => next.input().0,
+                None => accounts.user_out_ata,
+            };
+            hop.pool.transfer_out(hop.is_x, to, quote.vault_out)?;
+        }
+
+        // ============ 步骤4：发布交换结果 ============
+        SwapReturnData {
+            amount_in: self.instruction_data.amount_in,
+            amount_out: last.amount_out,
+            ..SwapReturnData::from(&last)
+        }
+        .set();
+
+        Ok(())
+    }
+}
@@ -1,23 +1,33 @@
-use crate::state::{AmmState, Config};
-use constant_product_curve::{ConstantProduct, LiquidityPair};
+use crate::{
+    error::AmmError,
+    pool::{Pool, PoolAccounts},
+    return_data::SwapReturnData,
+    state::Config,
+    token_interface::{
+        check_token_account, mint_unchecked, token_account_unchecked, TransferChecked,
+    },
+};
 use core::mem::size_of;
 
 use pinocchio::{
-    cpi::{Seed, Signer},
     error::ProgramError,
     sysvars::{clock::Clock, Sysvar},
     AccountView, ProgramResult,
 };
-use pinocchio_token::instructions::Transfer;
-use pinocchio_token::state::TokenAccount;
-use pinocchio_token::ID as TOKEN_PROGRAM_ID;
 
 /// ========== 交换指令所需的账户 ==========
 /// 
 /// 用户进行代币交换时所需的所有账户。
+/// 输出代币默认转入用户自己的代币账户，也可以指定任意所有者的接收账户（例如收款商户）。
 pub struct SwapAccounts<'a> {
     /// 执行交换的用户账户（必须是签名者）
     pub user: &'a AccountView,
+    /// LP 代币的 mint 账户（提供曲线所需的 LP 供应量）
+    pub mint_lp: &'a AccountView,
+    /// 代币 X 的 mint 账户
+    pub mint_x: &'a AccountView,
+    /// 代币 Y 的 mint 账户
+    pub mint_y: &'a AccountView,
     /// 用户的代币 X 关联代币账户（可能是输入或输出账户）
     pub user_x_ata: &'a AccountView,
     /// 用户的代币 Y 关联代币账户（可能是输入或输出账户）
@@ -28,16 +38,32 @@ pub struct SwapAccounts<'a> {
     pub vault_y: &'a AccountView,
     /// AMM 配置账户（包含池参数）
     pub config: &'a AccountView,
-    /// SPL Token 程序
-    pub token_program: &'a AccountView,
+    /// 代币 X 所属的 Token 程序
+    pub token_program_x: &'a AccountView,
+    /// 代币 Y 所属的 Token 程序
+    pub token_program_y: &'a AccountView,
+    /// 可选的输出代币接收账户（不提供时输出转入用户自己的代币账户）
+    pub recipient: Option<&'a AccountView>,
 }
 
 impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
     type Error = ProgramError;
 
     /// 验证和提取交换指令所需的账户
+    ///
+    /// 账户列表末尾可以追加一个输出代币的接收账户，再多出的账户返回 InvalidArgument。
     fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
-        let [user, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program] = accounts
+        let (accounts, recipient) = match accounts {
+            [accounts @ .., recipient] if accounts.len() == Self::LEN => (accounts, Some(recipient)),
+            // 账户多于主账户加一个接收账户，不能静默忽略
+            accounts if accounts.len() > Self::LEN + 1 => {
+                return Err(ProgramError::InvalidArgument)
+            }
+            accounts => (accounts, None),
+        };
+
+        let [user, mint_lp, mint_x, mint_y, user_x_ata, user_y_ata, vault_x, vault_y, config, token_program_x, token_program_y] =
+            accounts
         else {
             return Err(ProgramError::NotEnoughAccountKeys);
         };
@@ -47,48 +73,53 @@ impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        // ============ Config 账户验证 ============
-        // 验证 Config 账户的大小和所有权
-        if config.data_len() != Config::LEN {
-            return Err(ProgramError::InvalidAccountData);
-        }
-        if !config.owned_by(&crate::ID) {
-            return Err(ProgramError::InvalidAccountOwner);
+        // ============ 池账户验证 ============
+        // Config、LP mint、代币 mint、Token 程序和金库的验证与其他指令共用
+        PoolAccounts {
+            config,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
         }
+        .validate()?;
 
-        // ============ Token Program 验证 ============
-        // 确保使用的是真实的 SPL Token 程序
-        if token_program.address() != &TOKEN_PROGRAM_ID {
-            return Err(ProgramError::InvalidArgument);
-        }
+        // 验证用户代币账户由对应的 Token 程序拥有且格式正确
+        check_token_account(user_x_ata, token_program_x.address())?;
+        check_token_account(user_y_ata, token_program_y.address())?;
 
-        // ============ 代币账户格式和所有权验证 ============
-        // 验证所有相关的代币账户都由 Token 程序拥有且格式正确
-        if user_x_ata.data_len() != TokenAccount::LEN
-            || !user_x_ata.owned_by(token_program.address())
+        // ============ 用户代币账户验证 ============
+        // 用户账户持有的代币必须与 Config 中的 mint 对应，且归签名用户所有
         {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-        if user_y_ata.data_len() != TokenAccount::LEN
-            || !user_y_ata.owned_by(token_program.address())
-        {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-        if vault_x.data_len() != TokenAccount::LEN || !vault_x.owned_by(token_program.address()) {
-            return Err(ProgramError::InvalidAccountOwner);
-        }
-        if vault_y.data_len() != TokenAccount::LEN || !vault_y.owned_by(token_program.address()) {
-            return Err(ProgramError::InvalidAccountOwner);
+            let user_x_account = unsafe { token_account_unchecked(user_x_ata) };
+            let user_y_account = unsafe { token_account_unchecked(user_y_ata) };
+            if user_x_account.mint() != mint_x.address()
+                || user_y_account.mint() != mint_y.address()
+            {
+                return Err(AmmError::InvalidMint.into());
+            }
+            if user_x_account.owner() != user.address() || user_y_account.owner() != user.address()
+            {
+                return Err(AmmError::InvalidTokenAccountOwner.into());
+            }
         }
 
         Ok(SwapAccounts {
             user,
+            mint_lp,
+            mint_x,
+            mint_y,
             user_x_ata,
             user_y_ata,
             vault_x,
             vault_y,
             config,
-            token_program,
+            token_program_x,
+            token_program_y,
+            recipient,
         })
     }
 }
@@ -100,9 +131,9 @@ impl<'a> TryFrom<&'a [AccountView]> for SwapAccounts<'a> {
 pub struct SwapInstructionData {
     /// 标志位：true 表示用 X 换 Y，false 表示用 Y 换 X
     pub is_x: bool,
-    /// 用户想要交换的代币数量（输入代币的数量）
+    /// 用户想要交换的代币数量（输入代币从用户账户转出的数量）
     pub amount: u64,
-    /// 用户愿意接收的最少输出代币数量（滑点保护）
+    /// 用户账户实际到账的最少输出代币数量（滑点保护，已扣除 Token-2022 转账手续费）
     pub min: u64,
     /// 交易过期时间（Unix 时间戳，0 表示不限制）
     pub expiration: i64,
@@ -125,17 +156,17 @@ impl<'a> TryFrom<&'a [u8]> for SwapInstructionData {
         // 如果设置了过期时间，验证当前时间未超过
         let clock = pinocchio::sysvars::clock::Clock::get()?;
         if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
-            return Err(ProgramError::Custom(0));
+            return Err(AmmError::Expired.into());
         }
 
         // ============ 金额有效性检查 ==========
         // 验证交换数量大于 0
         if instruction_data.amount == 0 {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::ZeroAmount.into());
         }
         // 验证最小输出数量大于 0
         if instruction_data.min == 0 {
-            return Err(ProgramError::InvalidArgument);
+            return Err(AmmError::ZeroAmount.into());
         }
         
         Ok(instruction_data)
@@ -160,6 +191,9 @@ impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for Swap<'a> {
         let accounts = SwapAccounts::try_from(accounts)?;
         let instruction_data = SwapInstructionData::try_from(data)?;
 
+        // 验证可选的输出接收账户
+        accounts.output_account(instruction_data.is_x)?;
+
         Ok(Self {
             accounts,
             instruction_data,
@@ -175,107 +209,136 @@ impl<'a> Swap<'a> {
     /// 
     /// 根据 is_x 标志，执行 X→Y 或 Y→X 交换，
     /// 使用常数乘积曲线计算输出数量。
+    ///
+    /// 若代币带有 Token-2022 转账手续费，曲线按金库实际收到的输入数量计算，
+    /// 滑点检查按用户实际收到的输出数量进行（见 [`Pool::quote_swap`]）。
     pub fn process(&mut self) -> ProgramResult {
-        // ============ 步骤1：加载金库数据 ============
-        // 获取当前金库中的代币数量
-        let vault_x = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_x)? };
-        let vault_y = unsafe { TokenAccount::from_account_view_unchecked(self.accounts.vault_y)? };
-        
-        // ============ 步骤2：加载配置和验证状态 ============
-        // 获取 Config PDA 的数据备用（稍后用于生成签名）
-        let cfg2 = Config::load(self.accounts.config)?;
-        
-        // 验证 AMM 已初始化且处于可用状态
-        if cfg2.state() != (AmmState::Initialized as u8) {
-            return Err(ProgramError::InvalidAccountData);
+        // ============ 步骤1：加载配置和验证状态 ============
+        // 可变借用仅在计算和记账期间持有，转账前释放
+        let quote = {
+            let mut cfg = Config::load_mut(self.accounts.config)?;
+
+            // 验证 AMM 已初始化且处于可用状态
+            if !cfg.amm_state()?.allows_swap() {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // ============ 步骤2：计算交换结果 ============
+            // 与 Quote 指令共用同一计算路径，L 为实际的 LP 供应量
+            let pool = unsafe { self.accounts.pool(&cfg)? };
+            let quote = pool.quote_swap(
+                self.instruction_data.is_x,
+                self.instruction_data.amount,
+                self.instruction_data.min,
+            )?;
+
+            // 按协议分成比例从交换费用（以输入代币计）中累计协议费用
+            cfg.accrue_protocol_fee(self.instruction_data.is_x, quote.protocol_fee)?;
+
+            quote
+        };
+
+        // ============ 步骤3：执行代币转账 ============
+        self.accounts
+            .transfer(self.instruction_data.is_x, quote.amount_in, quote.vault_out)?;
+
+        // ============ 步骤4：发布交换结果 ============
+        SwapReturnData::from(&quote).set();
+
+        Ok(())
+    }
+}
+
+impl<'a> SwapAccounts<'a> {
+    /// 不含可选接收账户时的账户数量
+    pub const LEN: usize = 11;
+
+    /// 读取计算报价所需的池状态
+    ///
+    /// # Safety
+    /// 账户已在 `try_from` 中验证；调用期间不能存在金库或 mint 的可变借用。
+    pub unsafe fn pool(&self, config: &Config) -> Result<Pool, ProgramError> {
+        unsafe { self.pool_accounts().pool(config) }
+    }
+
+    /// 本池参与交换的账户（不含用户账户）
+    #[inline(always)]
+    pub fn pool_accounts(&self) -> PoolAccounts<'a> {
+        PoolAccounts {
+            config: self.config,
+            mint_lp: self.mint_lp,
+            mint_x: self.mint_x,
+            mint_y: self.mint_y,
+            vault_x: self.vault_x,
+            vault_y: self.vault_y,
+            token_program_x: self.token_program_x,
+            token_program_y: self.token_program_y,
         }
+    }
 
-        // ============ 步骤3：初始化常数乘积曲线模型 ============
-        // 创建曲线模型，用于计算交换数量
-        let mut curve = ConstantProduct::init(
-            vault_x.amount(),              // X 金库当前余额
-            vault_y.amount(),              // Y 金库当前余额
-            vault_x.amount(),              // X 金库的初始供应（用于计费）
-            cfg2.fee(),                    // 交换费用（以 bps 计）
-            None,                          // 自定义参数（无）
-        )
-        .map_err(|_| ProgramError::ArithmeticOverflow)?;
-
-        // ============ 步骤4：确定交换方向 ============
-        // 根据 is_x 确定用户输入的是 X 还是 Y
-        let p = match self.instruction_data.is_x {
-            true => LiquidityPair::X,    // 用户输入 X，输出 Y
-            false => LiquidityPair::Y,   // 用户输入 Y，输出 X
+    /// ========== 输出代币的接收账户 ==========
+    ///
+    /// 提供了接收账户时，验证它是输出代币的代币账户（其所有者可以是任何人）并返回；
+    /// 否则返回用户自己的输出代币账户。`is_x` 为 true 表示输入 X、输出 Y。
+    pub fn output_account(&self, is_x: bool) -> Result<&'a AccountView, ProgramError> {
+        let (user_out_ata, mint_out, token_program_out) = match is_x {
+            true => (self.user_y_ata, self.mint_y, self.token_program_y),
+            false => (self.user_x_ata, self.mint_x, self.token_program_x),
         };
 
-        // ============ 步骤5：计算交换结果 ============
-        // 使用常数乘积曲线计算输入和输出代币的数量
-        let swap_result = curve
-            .swap(p, self.instruction_data.amount, self.instruction_data.min)
-            .map_err(|_| ProgramError::InvalidArgument)?;
+        let Some(recipient) = self.recipient else {
+            return Ok(user_out_ata);
+        };
 
-        // 验证计算结果的有效性
-        if swap_result.deposit == 0 || swap_result.withdraw == 0 {
-            return Err(ProgramError::InvalidArgument);
+        check_token_account(recipient, token_program_out.address())?;
+        if unsafe { token_account_unchecked(recipient) }.mint() != mint_out.address() {
+            return Err(AmmError::InvalidMint.into());
         }
 
-        // ============ 步骤6：准备 Config PDA 签名 ============
-        // 构造用于签署转账交易的 PDA 签名种子
-        let seed_bytes = cfg2.seed().to_le_bytes();
-        let bump = cfg2.config_bump();
-        let config_seeds = [
-            Seed::from(b"config"),
-            Seed::from(&seed_bytes),
-            Seed::from(cfg2.mint_x().as_ref()),
-            Seed::from(cfg2.mint_y().as_ref()),
-            Seed::from(&bump),
-        ];
-        let signer = [Signer::from(&config_seeds)];
-
-        // ============ 步骤7：执行代币转账 ============
-        // 根据交换方向进行相应的转账操作
-        if self.instruction_data.is_x {
-            // 用户交换 X → Y 的情况
-            
-            // 将用户的代币 X 转入金库（用户签名）
-            Transfer {
-                from: self.accounts.user_x_ata,
-                to: self.accounts.vault_x,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
-            }
-            .invoke()?;
-
-            // 将金库的代币 Y 转给用户（Config PDA 签名）
-            Transfer {
-                from: self.accounts.vault_y,
-                to: self.accounts.user_y_ata,
-                authority: self.accounts.config,
-                amount: swap_result.withdraw,
-            }
-            .invoke_signed(&signer)?;
-        } else {
-            // 用户交换 Y → X 的情况
-            
-            // 将用户的代币 Y 转入金库（用户签名）
-            Transfer {
-                from: self.accounts.user_y_ata,
-                to: self.accounts.vault_y,
-                authority: self.accounts.user,
-                amount: swap_result.deposit,
-            }
-            .invoke()?;
-
-            // 将金库的代币 X 转给用户（Config PDA 签名）
-            Transfer {
-                from: self.accounts.vault_x,
-                to: self.accounts.user_x_ata,
-                authority: self.accounts.config,
-                amount: swap_result.withdraw,
-            }
-            .invoke_signed(&signer)?;
+        Ok(recipient)
+    }
+
+    /// 执行交换的两笔转账
+    ///
+    /// 用户将 `amount_in` 个输入代币转入金库（用户签名），
+    /// 金库将 `amount_out` 个输出代币转给输出接收账户（Config PDA 签名）。
+    /// `is_x` 为 true 表示输入 X、输出 Y。
+    pub fn transfer(&self, is_x: bool, amount_in: u64, amount_out: u64) -> ProgramResult {
+        // 将用户的输入代币转入金库（用户签名）
+        let (from, mint, to, token_program) = match is_x {
+            true => (
+                self.user_x_ata,
+                self.mint_x,
+                self.vault_x,
+                self.token_program_x,
+            ),
+            false => (
+                self.user_y_ata,
+                self.mint_y,
+                self.vault_y,
+                self.token_program_y,
+            ),
+        };
+        TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.user,
+            amount: amount_in,
+            decimals: unsafe { mint_unchecked(mint) }.decimals(),
+            token_program: token_program.address(),
         }
+        .invoke()?;
 
-        Ok(())
+        self.transfer_out(is_x, amount_out)
+    }
+
+    /// 将金库的 `amount_out` 个输出代币转给输出接收账户（Config PDA 签名）
+    ///
+    /// `is_x` 为 true 表示输入 X、输出 Y。Swap 在用户转入后调用，FlashSwap 在回调前调用。
+    pub fn transfer_out(&self, is_x: bool, amount_out: u64) -> ProgramResult {
+        let output_account = self.output_account(is_x)?;
+        self.pool_accounts()
+            .transfer_out(is_x, output_account, amount_out)
     }
 }
@@ -0,0 +1,131 @@
+use crate::{
+    error::AmmError, instructions::swap::SwapAccounts, return_data::SwapReturnData, state::Config,
+};
+use core::mem::size_of;
+
+use pinocchio::{
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+
+/// ========== 精确输出交换指令的数据结构 ==========
+///
+/// 用户指定想要收到的输出数量，以及愿意支付的最大输入数量。
+/// 账户列表与 Swap 指令完全相同。
+#[repr(C, packed)]
+pub struct SwapExactOutInstructionData {
+    /// 标志位：true 表示用 X 换 Y，false 表示用 Y 换 X
+    pub is_x: bool,
+    /// 用户账户实际到账的输出代币数量（已扣除 Token-2022 转账手续费）
+    pub amount_out: u64,
+    /// 用户愿意从账户转出的最大输入代币数量（滑点保护，含 Token-2022 转账手续费）
+    pub max_in: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for SwapExactOutInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析精确输出交换指令参数，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        const SWAP_EXACT_OUT_DATA_LEN: usize =
+            size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        if data.len() != SWAP_EXACT_OUT_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        // ============ 金额有效性检查 ==========
+        // 验证输出数量和最大输入数量大于 0
+        if instruction_data.amount_out == 0 || instruction_data.max_in == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== SwapExactOut 指令实现 ==========
+///
+/// 用户换出恰好 `amount_out` 个输出代币，所需输入由储备和费率反推，
+/// 取整向有利于池的方向，超过 `max_in` 则交易失败。
+pub struct SwapExactOut<'a> {
+    /// 所需的账户（与 Swap 相同）
+    pub accounts: SwapAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: SwapExactOutInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for SwapExactOut<'a> {
+    type Error = ProgramError;
+
+    /// 构建 SwapExactOut 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = SwapAccounts::try_from(accounts)?;
+        let instruction_data = SwapExactOutInstructionData::try_from(data)?;
+
+        // 验证可选的输出接收账户
+        accounts.output_account(instruction_data.is_x)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> SwapExactOut<'a> {
+    /// 该指令的鉴别器值（9 表示精确输出交换指令）
+    pub const DISCRIMINATOR: &'a u8 = &9;
+
+    /// 执行精确输出交换流程
+    ///
+    /// 若代币带有 Token-2022 转账手续费，金库转出的数量会补足输出侧手续费，
+    /// 用户转入的数量会补足输入侧手续费，`max_in` 约束的是用户实际转出的数量
+    /// （见 [`crate::pool::Pool::quote_swap_exact_out`]）。
+    pub fn process(&mut self) -> ProgramResult {
+        // ============ 步骤1：加载配置并计算所需输入 ============
+        // 可变借用仅在计算和记账期间持有，转账前释放
+        let quote = {
+            let mut cfg = Config::load_mut(self.accounts.config)?;
+
+            // 验证 AMM 已初始化且处于可用状态
+            if !cfg.amm_state()?.allows_swap() {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // 与 Quote 指令共用同一计算路径
+            let pool = unsafe { self.accounts.pool(&cfg)? };
+            let quote = pool.quote_swap_exact_out(
+                self.instruction_data.is_x,
+                self.instruction_data.amount_out,
+                self.instruction_data.max_in,
+            )?;
+
+            // 按协议分成比例从交换费用（以输入代币计）中累计协议费用
+            cfg.accrue_protocol_fee(self.instruction_data.is_x, quote.protocol_fee)?;
+
+            quote
+        };
+
+        // ============ 步骤2：执行代币转账 ============
+        self.accounts
+            .transfer(self.instruction_data.is_x, quote.amount_in, quote.vault_out)?;
+
+        // ============ 步骤3：发布交换结果 ============
+        SwapReturnData::from(&quote).set();
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use crate::{
+    error::AmmError,
+    state::{AmmState, Config},
+};
+use core::mem::size_of;
+
+use pinocchio::{error::ProgramError, AccountView, ProgramResult};
+
+/// ========== 更新配置指令所需的账户 ==========
+///
+/// 权限管理员修改池参数时所需的所有账户。
+pub struct UpdateConfigAccounts<'a> {
+    /// 池的权限管理员（必须是签名者，且与 Config 中存储的 authority 一致）
+    pub authority: &'a AccountView,
+    /// AMM 配置账户（将被修改）
+    pub config: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for UpdateConfigAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 验证和提取更新配置指令所需的账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [authority, config] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 验证权限管理员是交易签名者
+        if !authority.is_signer() {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { authority, config })
+    }
+}
+
+/// ========== 更新配置指令的数据结构 ==========
+///
+/// 包含池的新费率、新状态、协议分成比例和闪电贷费用，费率和状态未改变时需传入当前值。
+/// 末尾的协议分成比例和闪电贷费用可以依次省略，省略或传入对应的 `*_UNCHANGED` 时保持当前值不变，
+/// 因此只含费率和状态的旧格式仍然有效。
+#[repr(C, packed)]
+pub struct UpdateConfigInstructionData {
+    /// 新的交换费用（bps，范围 0-9999）
+    pub fee: u16,
+    /// 新的池状态（Initialized、Disabled 或 WithdrawOnly）
+    pub state: u8,
+    /// 新的协议分成比例（bps，范围 0-10000；`PROTOCOL_FEE_SHARE_UNCHANGED` 表示不修改）
+    pub protocol_fee_share: u16,
+    /// 新的闪电贷费用（bps，范围 0-9999；`FLASH_FEE_UNCHANGED` 表示不修改）
+    pub flash_fee: u16,
+}
+
+impl UpdateConfigInstructionData {
+    /// 协议分成比例字段的特殊值：保持当前协议分成比例不变
+    pub const PROTOCOL_FEE_SHARE_UNCHANGED: u16 = u16::MAX;
+
+    /// 闪电贷费用字段的特殊值：保持当前闪电贷费用不变
+    pub const FLASH_FEE_UNCHANGED: u16 = u16::MAX;
+}
+
+impl<'a> TryFrom<&'a [u8]> for UpdateConfigInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析更新配置指令参数，末尾的可选字段可以省略
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 计算不同格式的数据长度
+        const UPDATE_CONFIG_DATA_LEN_FULL: usize = size_of::<UpdateConfigInstructionData>();
+        const UPDATE_CONFIG_DATA_LEN_WITH_SHARE: usize =
+            UPDATE_CONFIG_DATA_LEN_FULL - size_of::<u16>();
+        const UPDATE_CONFIG_DATA_LEN: usize = UPDATE_CONFIG_DATA_LEN_WITH_SHARE - size_of::<u16>();
+
+        let instruction_data = match data.len() {
+            // 完整格式：包含 protocol_fee_share 和 flash_fee 字段
+            UPDATE_CONFIG_DATA_LEN_FULL => unsafe {
+                (data.as_ptr() as *const Self).read_unaligned()
+            },
+            // 简化格式：缺少 flash_fee，或同时缺少 protocol_fee_share 和 flash_fee
+            UPDATE_CONFIG_DATA_LEN | UPDATE_CONFIG_DATA_LEN_WITH_SHARE => {
+                // 省略的字段使用不修改标记，再用已提供的数据覆盖
+                let mut raw = [0u8; UPDATE_CONFIG_DATA_LEN_FULL];
+                raw[UPDATE_CONFIG_DATA_LEN..UPDATE_CONFIG_DATA_LEN_WITH_SHARE]
+                    .copy_from_slice(&Self::PROTOCOL_FEE_SHARE_UNCHANGED.to_le_bytes());
+                raw[UPDATE_CONFIG_DATA_LEN_WITH_SHARE..]
+                    .copy_from_slice(&Self::FLASH_FEE_UNCHANGED.to_le_bytes());
+                raw[..data.len()].copy_from_slice(data);
+                unsafe { (raw.as_ptr() as *const Self).read_unaligned() }
+            }
+            _ => return Err(ProgramError::InvalidInstructionData),
+        };
+
+        // 不允许将池重新置为未初始化状态
+        if instruction_data.state == AmmState::Uninitialized as u8 {
+            return Err(AmmError::InvalidState.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== UpdateConfig 指令实现 ==========
+///
+/// 权限管理员修改池的交换费用、协议分成比例、闪电贷费用和状态。
+/// 未设置权限管理员的池配置被永久锁定，任何人都无法修改。
+pub struct UpdateConfig<'a> {
+    /// 所需的账户
+    pub accounts: UpdateConfigAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: UpdateConfigInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for UpdateConfig<'a> {
+    type Error = ProgramError;
+
+    /// 构建 UpdateConfig 指令，验证签名者是池的权限管理员
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = UpdateConfigAccounts::try_from(accounts)?;
+        let instruction_data = UpdateConfigInstructionData::try_from(data)?;
+
+        // ============ 权限验证 ============
+        // 没有权限管理员的池不可修改；有则必须由其签名
+        let config = Config::load(accounts.config)?;
+        config.check_authority(accounts.authority.address())?;
+
+        // 未初始化的池不能修改配置
+        if config.state() == AmmState::Uninitialized as u8 {
+            return Err(AmmError::PoolDisabled.into());
+        }
+
+        drop(config);
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> UpdateConfig<'a> {
+    /// 该指令的鉴别器值（4 表示更新配置指令）
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    /// 执行更新配置流程
+    ///
+    /// 将新的费率和状态写入 Config 账户，协议分成比例和闪电贷费用仅在未标记为不修改时写入，
+    /// setter 会校验取值范围。
+    pub fn process(&mut self) -> ProgramResult {
+        let mut config = Config::load_mut(self.accounts.config)?;
+
+        // 闪电贷回调期间池参数必须保持不变，不允许修改费率或状态
+        config.check_no_flash_loan()?;
+
+        config.set_fee(self.instruction_data.fee)?;
+        if self.instruction_data.protocol_fee_share
+            != UpdateConfigInstructionData::PROTOCOL_FEE_SHARE_UNCHANGED
+        {
+            config.set_protocol_fee_share(self.instruction_data.protocol_fee_share)?;
+        }
+        if self.instruction_data.flash_fee != UpdateConfigInstructionData::FLASH_FEE_UNCHANGED {
+            config.set_flash_fee(self.instruction_data.flash_fee)?;
+        }
+        config.set_state(self.instruction_data.state)?;
+
+        Ok(())
+    }
+}
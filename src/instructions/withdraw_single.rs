@@ -0,0 +1,175 @@
+use crate::{
+    error::AmmError,
+    instructions::withdraw::WithdrawAccounts,
+    return_data::LiquidityReturnData,
+    state::Config,
+    token_interface::{mint_unchecked, TransferChecked},
+};
+use core::mem::size_of;
+
+use pinocchio::{
+    cpi::Signer,
+    error::ProgramError,
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, ProgramResult,
+};
+use pinocchio_token::instructions::Burn;
+
+/// ========== 单边提取指令的数据结构 ==========
+///
+/// 用户销毁 LP，只取回一侧代币，指定实际到账的最少数量。
+/// 账户列表与 Withdraw 指令完全相同，另一侧的用户代币账户不会收到代币。
+#[repr(C, packed)]
+pub struct WithdrawSingleInstructionData {
+    /// 标志位：true 表示取回代币 X，false 表示取回代币 Y
+    pub is_x: bool,
+    /// 用户想要销毁的 LP 代币数量
+    pub amount: u64,
+    /// 用户账户实际到账的最少输出代币数量（滑点保护，已扣除 Token-2022 转账手续费）
+    pub min_out: u64,
+    /// 交易过期时间（Unix 时间戳，0 表示不限制）
+    pub expiration: i64,
+}
+
+impl<'a> TryFrom<&'a [u8]> for WithdrawSingleInstructionData {
+    type Error = ProgramError;
+
+    /// 从字节数据解析单边提取指令参数，进行有效性检查
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        // 验证数据长度与预期结构相符
+        const WITHDRAW_SINGLE_DATA_LEN: usize =
+            size_of::<bool>() + size_of::<u64>() * 2 + size_of::<i64>();
+        if data.len() != WITHDRAW_SINGLE_DATA_LEN {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+
+        let instruction_data = unsafe { (data.as_ptr() as *const Self).read_unaligned() };
+
+        // ============ 过期时间检查 ==========
+        // 如果设置了过期时间，验证当前时间未超过
+        let clock = Clock::get()?;
+        if instruction_data.expiration != 0 && clock.unix_timestamp > instruction_data.expiration {
+            return Err(AmmError::Expired.into());
+        }
+
+        // 验证 LP 销毁数量大于 0
+        if instruction_data.amount == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        Ok(instruction_data)
+    }
+}
+
+/// ========== WithdrawSingle 指令实现 ==========
+///
+/// 用户销毁 LP 只取回一侧代币（zap out）：按比例提取的另一侧代币按池价格和交换费用
+/// 隐含地换成输出侧代币，换入的代币从未离开金库，整个过程在同一笔指令内原子完成。
+pub struct WithdrawSingle<'a> {
+    /// 所需的账户（与 Withdraw 相同）
+    pub accounts: WithdrawAccounts<'a>,
+    /// 指令参数
+    pub instruction_data: WithdrawSingleInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountView])> for WithdrawSingle<'a> {
+    type Error = ProgramError;
+
+    /// 构建 WithdrawSingle 指令
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountView])) -> Result<Self, Self::Error> {
+        let accounts = WithdrawAccounts::try_from(accounts)?;
+        let instruction_data = WithdrawSingleInstructionData::try_from(data)?;
+
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> WithdrawSingle<'a> {
+    /// 该指令的鉴别器值（12 表示单边提取指令）
+    pub const DISCRIMINATOR: &'a u8 = &12;
+
+    /// 执行单边提取流程
+    ///
+    /// 隐含交换与 Swap 使用同一曲线和费率，交换费用中的协议分成同样计入待提取的协议费用。
+    /// 若输出代币带有 Token-2022 转账手续费，`min_out` 约束的是用户实际到账的数量
+    /// （见 [`crate::pool::Pool::quote_withdraw_single`]）。
+    pub fn process(&mut self) -> ProgramResult {
+        // ============ 步骤1：加载配置并计算输出数量 ============
+        // 可变借用仅在计算和记账期间持有，转账前释放
+        let quote = {
+            let mut cfg = Config::load_mut(self.accounts.pool.config)?;
+
+            // 单边提取包含隐含交换，池状态需同时允许提取和交换
+            let state = cfg.amm_state()?;
+            if !(state.allows_withdraw() && state.allows_swap()) {
+                return Err(AmmError::PoolDisabled.into());
+            }
+
+            // 与 Quote 指令共用同一计算路径
+            let pool = unsafe { self.accounts.pool.pool(&cfg)? };
+            let quote = pool.quote_withdraw_single(
+                self.instruction_data.is_x,
+                self.instruction_data.amount,
+                self.instruction_data.min_out,
+            )?;
+
+            // 隐含交换的输入是另一侧代币，协议费用以另一侧代币计
+            cfg.accrue_protocol_fee(!self.instruction_data.is_x, quote.protocol_fee)?;
+
+            quote
+        };
+
+        // ============ 步骤2：销毁用户的 LP 代币 ============
+        // 用户授权销毁操作
+        Burn {
+            account: self.accounts.user_lp_ata,
+            mint: self.accounts.pool.mint_lp,
+            authority: self.accounts.user,
+            amount: self.instruction_data.amount,
+        }
+        .invoke()?;
+
+        // ============ 步骤3：从金库转出输出代币给用户 ============
+        // Config PDA 是金库的权限方，需要其签名
+        let cfg = Config::load(self.accounts.pool.config)?;
+        let config_seeds = cfg.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        let (from, mint, to, token_program, amount) = match self.instruction_data.is_x {
+            true => (
+                self.accounts.pool.vault_x,
+                self.accounts.pool.mint_x,
+                self.accounts.user_x_ata,
+                self.accounts.pool.token_program_x,
+                quote.withdraw.vault_x,
+            ),
+            false => (
+                self.accounts.pool.vault_y,
+                self.accounts.pool.mint_y,
+                self.accounts.user_y_ata,
+                self.accounts.pool.token_program_y,
+                quote.withdraw.vault_y,
+            ),
+        };
+
+        TransferChecked {
+            from,
+            mint,
+            to,
+            authority: self.accounts.pool.config,
+            amount,
+            decimals: unsafe { mint_unchecked(mint) }.decimals(),
+            token_program: token_program.address(),
+        }
+        .invoke_signed(&signer)?;
+
+        // ============ 步骤4：发布提取结果 ============
+        // 返回用户实际到账的数量（已扣除 Token-2022 转账手续费）
+        LiquidityReturnData::from(&quote).set();
+
+        Ok(())
+    }
+}
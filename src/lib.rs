@@ -3,6 +3,10 @@ use pinocchio::{
 };
 entrypoint!(process_instruction);
 
+/// 错误模块 - 定义了 AMM 合约的自定义错误码
+pub mod error;
+pub use error::*;
+
 /// 指令模块 - 包含所有智能合约可执行的指令
 pub mod instructions;
 pub use instructions::*;
@@ -11,6 +15,17 @@ pub use instructions::*;
 pub mod state;
 pub use state::*;
 
+/// 报价模块 - 交换、存入、提取与报价指令共用的计算路径
+pub mod pool;
+pub use pool::*;
+
+/// 返回数据模块 - 定义了指令通过 set_return_data 发布的结果布局
+pub mod return_data;
+pub use return_data::*;
+
+/// Token 程序兼容层 - 统一处理 SPL Token 与 Token-2022
+pub mod token_interface;
+
 declare_id!("22222222222222222222222222222222222222222222");
 
 /// 主指令入口函数
@@ -38,6 +53,19 @@ fn process_instruction(
                 1 => Deposit::try_from((data, accounts))?.process(),         // 存入流动性
                 2 => Withdraw::try_from((data, accounts))?.process(),        // 提取流动性
                 3 => Swap::try_from((data, accounts))?.process(),            // 交换代币
+                4 => UpdateConfig::try_from((data, accounts))?.process(),    // 更新池配置
+                5 => ProposeAuthority::try_from((data, accounts))?.process(), // 提议转移权限
+                6 => AcceptAuthority::try_from((data, accounts))?.process(),  // 接受权限转移
+                7 => RenounceAuthority::try_from((data, accounts))?.process(), // 放弃权限
+                8 => CollectProtocolFees::try_from((data, accounts))?.process(), // 提取协议费用
+                9 => SwapExactOut::try_from((data, accounts))?.process(),   // 精确输出交换
+                10 => Quote::try_from((data, accounts))?.process(),         // 只读报价
+                11 => DepositSingle::try_from((data, accounts))?.process(), // 单边存入
+                12 => WithdrawSingle::try_from((data, accounts))?.process(), // 单边提取
+                13 => DepositByAmounts::try_from((data, accounts))?.process(), // 按代币数量存入
+                14 => FlashLoan::try_from((data, accounts))?.process(),     // 闪电贷
+                15 => FlashSwap::try_from((data, accounts))?.process(),     // 闪电交换
+                16 => RouteSwap::try_from((data, accounts))?.process(),     // 路由交换
                 _ => Err(ProgramError::InvalidInstructionData),              // 未知指令
             }
         }
@@ -0,0 +1,1703 @@
+use crate::{
+    error::AmmError,
+    state::{Config, MINIMUM_LIQUIDITY},
+    token_interface::{
+        check_token_account, mint_unchecked, token_account_unchecked, transfer_fee,
+        TransferChecked, TransferFee,
+    },
+};
+use constant_product_curve::{ConstantProduct, LiquidityPair, SwapResult};
+
+use pinocchio::{cpi::Signer, error::ProgramError, AccountView, Address, ProgramResult};
+use pinocchio_token::state::Mint;
+use pinocchio_token::ID as TOKEN_PROGRAM_ID;
+
+/// ========== 池报价计算 ==========
+///
+/// 交换、存入、提取以及只读的 Quote 指令共用的计算路径。
+/// 各指令先将池的当前状态读入 [`Pool`]，再调用对应的报价方法；
+/// 报价只做计算，不移动代币，指令根据报价结果执行转账和记账。
+
+/// ========== 池状态快照 ==========
+///
+/// 计算报价所需的全部池参数，储备已扣除尚未提取的协议费用。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    /// 代币 X 净储备
+    pub reserve_x: u64,
+    /// 代币 Y 净储备
+    pub reserve_y: u64,
+    /// LP 代币供应量
+    pub lp_supply: u64,
+    /// 交换费用（bps）
+    pub fee: u16,
+    /// 协议分成比例（bps）
+    pub protocol_fee_share: u16,
+    /// 代币 X 当前生效的 Token-2022 转账手续费
+    pub transfer_fee_x: TransferFee,
+    /// 代币 Y 当前生效的 Token-2022 转账手续费
+    pub transfer_fee_y: TransferFee,
+}
+
+/// ========== 交换报价 ==========
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// 从用户账户转出的输入代币数量（含转账手续费）
+    pub amount_in: u64,
+    /// 接收账户实际到账的输出代币数量（已扣除转账手续费）
+    pub amount_out: u64,
+    /// 金库转出的输出代币数量
+    pub vault_out: u64,
+    /// 交换费用（以输入代币计，含协议分成）
+    pub fee: u64,
+    /// 交换费用中归协议的部分
+    pub protocol_fee: u64,
+    /// 交换后的代币 X 净储备
+    pub reserve_x: u64,
+    /// 交换后的代币 Y 净储备
+    pub reserve_y: u64,
+    /// 价格影响（bps）：成交价（不含交换费用）相对交换前现货价格的偏离
+    pub price_impact_bps: u64,
+}
+
+/// ========== 存入报价 ==========
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositQuote {
+    /// 从用户账户转出的代币 X 数量（含转账手续费）
+    pub amount_x: u64,
+    /// 从用户账户转出的代币 Y 数量（含转账手续费）
+    pub amount_y: u64,
+    /// 铸造给用户的 LP 数量（引导存入时已扣除锁定的最小流动性）
+    pub amount_lp: u64,
+    /// 是否为引导存入（池中尚无 LP）
+    pub is_bootstrap: bool,
+    /// 存入后的代币 X 净储备
+    pub reserve_x: u64,
+    /// 存入后的代币 Y 净储备
+    pub reserve_y: u64,
+}
+
+/// ========== 单边存入报价 ==========
+///
+/// 单边存入相当于先将部分输入按池价格和费率换成另一侧代币，再按交换后的储备比例存入，
+/// 换出的代币从未离开金库，因此只有输入侧代币发生转账。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepositSingleQuote {
+    /// 存入结果：输入侧为从用户账户转出的数量（含转账手续费），另一侧为 0
+    pub deposit: DepositQuote,
+    /// 金库收到的输入中，隐含交换的输入数量
+    pub swap_in: u64,
+    /// 隐含交换换出的另一侧代币数量（留在金库中一并存入）
+    pub swap_out: u64,
+    /// 隐含交换支付的交换费用（以输入代币计，含协议分成）
+    pub fee: u64,
+    /// 交换费用中归协议的部分
+    pub protocol_fee: u64,
+}
+
+/// ========== 提取报价 ==========
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawQuote {
+    /// 金库转出的代币 X 数量
+    pub vault_x: u64,
+    /// 金库转出的代币 Y 数量
+    pub vault_y: u64,
+    /// 用户实际到账的代币 X 数量（已扣除转账手续费）
+    pub amount_x: u64,
+    /// 用户实际到账的代币 Y 数量（已扣除转账手续费）
+    pub amount_y: u64,
+    /// 销毁的 LP 数量
+    pub amount_lp: u64,
+    /// 提取后的代币 X 净储备
+    pub reserve_x: u64,
+    /// 提取后的代币 Y 净储备
+    pub reserve_y: u64,
+}
+
+/// ========== 单边提取报价 ==========
+///
+/// 单边提取相当于先按比例提取两侧代币，再把另一侧按池价格和费率换成输出侧代币，
+/// 换入的代币从未离开金库，因此只有输出侧代币发生转账。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawSingleQuote {
+    /// 提取结果：输出侧为金库转出和用户实际到账的数量，另一侧为 0
+    pub withdraw: WithdrawQuote,
+    /// 按比例提取的另一侧代币中，隐含交换的输入数量（留在金库中）
+    pub swap_in: u64,
+    /// 隐含交换换出的输出侧代币数量
+    pub swap_out: u64,
+    /// 隐含交换支付的交换费用（以另一侧代币计，含协议分成）
+    pub fee: u64,
+    /// 交换费用中归协议的部分
+    pub protocol_fee: u64,
+}
+
+impl Pool {
+    /// ========== 读取池状态 ==========
+    ///
+    /// 从 Config、金库、LP mint 和代币 mint 读取当前状态。
+    ///
+    /// 闪电贷进行中时金库余额不代表真实储备，返回 FlashLoanActive，
+    /// 因此所有依赖报价的指令（交换、存入、提取及 Quote）都受重入保护。
+    ///
+    /// # Safety
+    /// 调用者必须已验证金库、LP mint 和代币 mint 属于该 Config，且不存在并发的可变借用。
+    pub unsafe fn load(
+        config: &Config,
+        vault_x: &AccountView,
+        vault_y: &AccountView,
+        mint_lp: &AccountView,
+        mint_x: &AccountView,
+        mint_y: &AccountView,
+    ) -> Result<Self, ProgramError> {
+        config.check_no_flash_loan()?;
+
+        let vault_x = unsafe { token_account_unchecked(vault_x) };
+        let vault_y = unsafe { token_account_unchecked(vault_y) };
+        let mint_lp =
+            unsafe { Mint::from_bytes_unchecked(&mint_lp.borrow_unchecked()[..Mint::LEN]) };
+
+        // 扣除尚未提取的协议费用，得到属于 LP 的净储备
+        let (reserve_x, reserve_y) = config.reserves(vault_x.amount(), vault_y.amount())?;
+
+        Ok(Self {
+            reserve_x,
+            reserve_y,
+            lp_supply: mint_lp.supply(),
+            fee: config.fee(),
+            protocol_fee_share: config.protocol_fee_share(),
+            transfer_fee_x: transfer_fee(mint_x)?,
+            transfer_fee_y: transfer_fee(mint_y)?,
+        })
+    }
+
+    /// 按交换方向返回（输入侧，输出侧）的转账手续费
+    #[inline(always)]
+    fn transfer_fees(&self, is_x: bool) -> (TransferFee, TransferFee) {
+        match is_x {
+            true => (self.transfer_fee_x, self.transfer_fee_y),
+            false => (self.transfer_fee_y, self.transfer_fee_x),
+        }
+    }
+
+    /// ========== 精确输入交换报价 ==========
+    ///
+    /// 用户转出 `amount_in` 个输入代币，曲线按金库实际收到的数量计算，
+    /// 用户实际到账的输出少于 `min_out` 时返回 SlippageExceeded。
+    pub fn quote_swap(
+        &self,
+        is_x: bool,
+        amount_in: u64,
+        min_out: u64,
+    ) -> Result<SwapQuote, ProgramError> {
+        let (transfer_fee_in, transfer_fee_out) = self.transfer_fees(is_x);
+
+        // 输入代币扣除转账手续费后才是金库实际收到的数量
+        let vault_in = transfer_fee_in.post_fee_amount(amount_in)?;
+        if vault_in == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        let swap_result = compute_swap(
+            self.reserve_x,
+            self.reserve_y,
+            self.lp_supply,
+            self.fee,
+            is_x,
+            vault_in,
+            min_out,
+        )?;
+
+        // 输出代币扣除转账手续费后才是用户实际收到的数量
+        let amount_out = transfer_fee_out.post_fee_amount(swap_result.withdraw)?;
+        if amount_out < min_out {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        self.swap_quote(is_x, amount_in, vault_in, amount_out, &swap_result)
+    }
+
+    /// ========== 精确输出交换报价 ==========
+    ///
+    /// 用户实际到账恰好 `amount_out` 个输出代币，所需输入取整向有利于池的方向，
+    /// 用户需要转出的输入超过 `max_in` 时返回 SlippageExceeded。
+    pub fn quote_swap_exact_out(
+        &self,
+        is_x: bool,
+        amount_out: u64,
+        max_in: u64,
+    ) -> Result<SwapQuote, ProgramError> {
+        let (transfer_fee_in, transfer_fee_out) = self.transfer_fees(is_x);
+
+        // 为使用户实际收到 amount_out，金库需要转出的数量
+        let vault_out = transfer_fee_out.pre_fee_amount(amount_out)?;
+
+        let swap_result = compute_swap_exact_out(
+            self.reserve_x,
+            self.reserve_y,
+            self.lp_supply,
+            self.fee,
+            is_x,
+            vault_out,
+        )?;
+
+        // 金库需要实际收到 swap_result.deposit，按转账手续费补足用户转出数量
+        let amount_in = transfer_fee_in.pre_fee_amount(swap_result.deposit)?;
+        if amount_in > max_in {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+        let vault_in = transfer_fee_in.post_fee_amount(amount_in)?;
+
+        self.swap_quote(is_x, amount_in, vault_in, amount_out, &swap_result)
+    }
+
+    /// 根据曲线结果汇总交换报价：协议费用、交换后净储备和价格影响
+    fn swap_quote(
+        &self,
+        is_x: bool,
+        amount_in: u64,
+        vault_in: u64,
+        amount_out: u64,
+        swap_result: &SwapResult,
+    ) -> Result<SwapQuote, ProgramError> {
+        let protocol_fee =
+            (swap_result.fee as u128 * self.protocol_fee_share as u128 / 10_000) as u64;
+
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.reserve_x, self.reserve_y),
+            false => (self.reserve_y, self.reserve_x),
+        };
+
+        // 输入侧：金库收到的数量中，协议费用部分记入待提取，不计入净储备
+        let new_reserve_in = reserve_in
+            .checked_add(vault_in)
+            .and_then(|r| r.checked_sub(protocol_fee))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let new_reserve_out = reserve_out
+            .checked_sub(swap_result.withdraw)
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        let (reserve_x, reserve_y) = match is_x {
+            true => (new_reserve_in, new_reserve_out),
+            false => (new_reserve_out, new_reserve_in),
+        };
+
+        // 价格影响 = 1 - (输出 / 扣费后输入) / (输出储备 / 输入储备)
+        let effective_in = swap_result.deposit.saturating_sub(swap_result.fee) as u128;
+        let price_impact_bps = match effective_in * reserve_out as u128 {
+            0 => 0,
+            denominator => {
+                let ratio =
+                    swap_result.withdraw as u128 * reserve_in as u128 * 10_000 / denominator;
+                10_000u128.saturating_sub(ratio) as u64
+            }
+        };
+
+        Ok(SwapQuote {
+            amount_in,
+            amount_out,
+            vault_out: swap_result.withdraw,
+            fee: swap_result.fee,
+            protocol_fee,
+            reserve_x,
+            reserve_y,
+            price_impact_bps,
+        })
+    }
+
+    /// ========== 存入报价 ==========
+    ///
+    /// 计算铸造 `amount` 个 LP 所需的代币 X 和 Y（含转账手续费），超过 `max_x` / `max_y`
+    /// 时返回 SlippageExceeded。池中尚无 LP 时为引导存入：`max_x` / `max_y` 全部存入并确定初始价格，
+    /// 铸造的 LP 总量为金库收到数量的几何平均数（`amount` 为可接受的最少 LP 总量），
+    /// 其中 `MINIMUM_LIQUIDITY` 个 LP 被永久锁定。
+    pub fn quote_deposit(
+        &self,
+        amount: u64,
+        max_x: u64,
+        max_y: u64,
+    ) -> Result<DepositQuote, ProgramError> {
+        let (amount_x, amount_y, amount_lp, is_bootstrap) = match self.lp_supply == 0 {
+            // 引导存入：池中尚无 LP，由首个存入者按 max_x / max_y 确定初始价格
+            true => {
+                // 扣除转账手续费后金库仍需收到非零数量，否则无法确定初始价格
+                let vault_x = self.transfer_fee_x.post_fee_amount(max_x)?;
+                let vault_y = self.transfer_fee_y.post_fee_amount(max_y)?;
+                if vault_x == 0 || vault_y == 0 {
+                    return Err(AmmError::ZeroAmount.into());
+                }
+                // LP 总量由存入的代币决定，首个存入者不能任意指定，
+                // 否则以极少的代币铸造海量 LP 会使锁定的最小流动性失去保护作用
+                let total_lp = u64::try_from(isqrt(vault_x as u128 * vault_y as u128))
+                    .map_err(|_| ProgramError::ArithmeticOverflow)?;
+                if total_lp < amount {
+                    return Err(AmmError::SlippageExceeded.into());
+                }
+                // 铸造数量必须足以覆盖永久锁定的最小流动性
+                if total_lp <= MINIMUM_LIQUIDITY {
+                    return Err(AmmError::InsufficientInitialLiquidity.into());
+                }
+                (max_x, max_y, total_lp - MINIMUM_LIQUIDITY, true)
+            }
+            // 常规存入：按当前储备比例计算铸造 amount 个 LP 所需的 X 和 Y（向上取整，有利于池）
+            false => {
+                let vault_x = proportional_amount(self.reserve_x, amount, self.lp_supply, true)?;
+                let vault_y = proportional_amount(self.reserve_y, amount, self.lp_supply, true)?;
+
+                // 任一侧所需数量为 0 时不能铸造 LP，否则 LP 可以免费获得
+                if vault_x == 0 || vault_y == 0 {
+                    return Err(AmmError::ZeroAmount.into());
+                }
+
+                // 金库需要实际收到 vault_x / vault_y，按转账手续费补足转出数量
+                let x = transfer_in_amount(self.transfer_fee_x, vault_x, max_x)?;
+                let y = transfer_in_amount(self.transfer_fee_y, vault_y, max_y)?;
+
+                (x, y, amount, false)
+            }
+        };
+
+        // 验证所需数量未超过用户愿意支付的上限
+        if !(amount_x <= max_x && amount_y <= max_y) {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        Ok(DepositQuote {
+            amount_x,
+            amount_y,
+            amount_lp,
+            is_bootstrap,
+            reserve_x: self
+                .reserve_x
+                .checked_add(self.transfer_fee_x.post_fee_amount(amount_x)?)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+            reserve_y: self
+                .reserve_y
+                .checked_add(self.transfer_fee_y.post_fee_amount(amount_y)?)
+                .ok_or(ProgramError::ArithmeticOverflow)?,
+        })
+    }
+
+    /// ========== 按代币数量存入报价 ==========
+    ///
+    /// 用户给出愿意存入的代币 X 和 Y 数量（含转账手续费），取其中符合当前储备比例的最大部分，
+    /// 铸造对应的 LP，多余的代币留在用户账户中。数量计算与 [`Pool::quote_deposit`] 共用同一路径。
+    /// 池中尚无 LP 时为引导存入：全部存入，LP 数量为金库收到数量的几何平均数，
+    /// 其中 `MINIMUM_LIQUIDITY` 个 LP 被永久锁定。用户获得的 LP 少于 `min_lp` 时返回 SlippageExceeded。
+    pub fn quote_deposit_by_amounts(
+        &self,
+        amount_x: u64,
+        amount_y: u64,
+        min_lp: u64,
+    ) -> Result<DepositQuote, ProgramError> {
+        // 扣除转账手续费后金库实际收到的数量
+        let vault_x = self.transfer_fee_x.post_fee_amount(amount_x)? as u128;
+        let vault_y = self.transfer_fee_y.post_fee_amount(amount_y)? as u128;
+
+        let amount = match self.lp_supply == 0 {
+            // 引导存入：LP 数量为 sqrt(x * y)，与具体价格无关
+            true => isqrt(vault_x * vault_y),
+            // 常规存入：按两侧各自可铸造的 LP 取较小值（向下取整），较大一侧只存入符合比例的部分
+            false => {
+                let lp_supply = self.lp_supply as u128;
+                let lp_from_x = (lp_supply * vault_x)
+                    .checked_div(self.reserve_x as u128)
+                    .ok_or(ProgramError::from(AmmError::CurveError))?;
+                let lp_from_y = (lp_supply * vault_y)
+                    .checked_div(self.reserve_y as u128)
+                    .ok_or(ProgramError::from(AmmError::CurveError))?;
+                lp_from_x.min(lp_from_y)
+            }
+        };
+        let amount = u64::try_from(amount).map_err(|_| ProgramError::ArithmeticOverflow)?;
+        if amount == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        // 以用户给出的数量作为上限，按 LP 数量计算实际转出的代币
+        let quote = self.quote_deposit(amount, amount_x, amount_y)?;
+        if quote.amount_lp < min_lp {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        Ok(quote)
+    }
+
+    /// ========== 单边存入报价 ==========
+    ///
+    /// 用户只转出 `amount_in` 个输入代币（`is_x` 为 true 表示代币 X）。金库收到的输入中，
+    /// 先按池价格和交换费用换出另一侧代币，使剩余输入与换得的代币恰好符合交换后的储备比例，
+    /// 再按两侧中较小的比例铸造 LP，取整产生的零头留在池中。
+    /// 铸造的 LP 少于 `min_lp` 时返回 SlippageExceeded，池中尚无流动性时返回 EmptyPool。
+    pub fn quote_deposit_single(
+        &self,
+        is_x: bool,
+        amount_in: u64,
+        min_lp: u64,
+    ) -> Result<DepositSingleQuote, ProgramError> {
+        // 单边存入按池价格计算，空池没有价格
+        if self.lp_supply == 0 || self.reserve_x == 0 || self.reserve_y == 0 {
+            return Err(AmmError::EmptyPool.into());
+        }
+
+        let (transfer_fee_in, _) = self.transfer_fees(is_x);
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.reserve_x, self.reserve_y),
+            false => (self.reserve_y, self.reserve_x),
+        };
+
+        // 输入代币扣除转账手续费后才是金库实际收到的数量
+        let vault_in = transfer_fee_in.post_fee_amount(amount_in)?;
+        if vault_in == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+
+        // ============ 隐含交换 ============
+        // 与 Swap 共用同一曲线计算，协议分成同样从交换费用中扣出
+        let swap_in = zap_swap_amount(reserve_in, vault_in, self.fee)?;
+        let swap_result = compute_swap(
+            self.reserve_x,
+            self.reserve_y,
+            self.lp_supply,
+            self.fee,
+            is_x,
+            swap_in,
+            1,
+        )?;
+        let protocol_fee =
+            (swap_result.fee as u128 * self.protocol_fee_share as u128 / 10_000) as u64;
+
+        // 交换后的净储备
+        let swapped_reserve_in = reserve_in
+            .checked_add(swap_result.deposit)
+            .and_then(|r| r.checked_sub(protocol_fee))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let swapped_reserve_out = reserve_out
+            .checked_sub(swap_result.withdraw)
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+
+        // ============ 按比例存入 ============
+        // 剩余输入与换得的代币按交换后的储备比例存入，取两侧中较小的 LP 数量（向下取整）
+        let deposit_in = vault_in - swap_result.deposit;
+        let lp_from_in = self.lp_supply as u128 * deposit_in as u128 / swapped_reserve_in as u128;
+        let lp_from_out =
+            self.lp_supply as u128 * swap_result.withdraw as u128 / swapped_reserve_out as u128;
+        let amount_lp = u64::try_from(lp_from_in.min(lp_from_out))
+            .map_err(|_| ProgramError::ArithmeticOverflow)?;
+
+        if amount_lp == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+        if amount_lp < min_lp {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        // 换出的代币从未离开金库：输出侧储备不变，输入侧增加金库收到的全部输入（扣除协议费用）
+        let new_reserve_in = reserve_in
+            .checked_add(vault_in)
+            .and_then(|r| r.checked_sub(protocol_fee))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let (amount_x, amount_y, reserve_x, reserve_y) = match is_x {
+            true => (amount_in, 0, new_reserve_in, reserve_out),
+            false => (0, amount_in, reserve_out, new_reserve_in),
+        };
+
+        Ok(DepositSingleQuote {
+            deposit: DepositQuote {
+                amount_x,
+                amount_y,
+                amount_lp,
+                is_bootstrap: false,
+                reserve_x,
+                reserve_y,
+            },
+            swap_in: swap_result.deposit,
+            swap_out: swap_result.withdraw,
+            fee: swap_result.fee,
+            protocol_fee,
+        })
+    }
+
+    /// ========== 提取报价 ==========
+    ///
+    /// 计算销毁 `amount` 个 LP 可以获得的代币 X 和 Y，
+    /// 用户实际到账数量低于 `min_x` / `min_y` 时返回 SlippageExceeded。
+    pub fn quote_withdraw(
+        &self,
+        amount: u64,
+        min_x: u64,
+        min_y: u64,
+    ) -> Result<WithdrawQuote, ProgramError> {
+        // 按销毁的 LP 占供应量的比例计算两侧数量（向下取整，有利于池）；
+        // 销毁全部 LP 时恰好为池中全部净储备
+        if amount > self.lp_supply {
+            return Err(AmmError::CurveError.into());
+        }
+        let x = proportional_amount(self.reserve_x, amount, self.lp_supply, false)?;
+        let y = proportional_amount(self.reserve_y, amount, self.lp_supply, false)?;
+
+        // 验证扣除转账手续费后用户实际收到的数量满足最小要求
+        let amount_x = self.transfer_fee_x.post_fee_amount(x)?;
+        let amount_y = self.transfer_fee_y.post_fee_amount(y)?;
+        if !(amount_x >= min_x && amount_y >= min_y) {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        Ok(WithdrawQuote {
+            vault_x: x,
+            vault_y: y,
+            amount_x,
+            amount_y,
+            amount_lp: amount,
+            reserve_x: self
+                .reserve_x
+                .checked_sub(x)
+                .ok_or(ProgramError::from(AmmError::CurveError))?,
+            reserve_y: self
+                .reserve_y
+                .checked_sub(y)
+                .ok_or(ProgramError::from(AmmError::CurveError))?,
+        })
+    }
+
+    /// ========== 单边提取报价 ==========
+    ///
+    /// 销毁 `amount` 个 LP，只取回一侧代币（`is_x` 为 true 表示代币 X）。
+    /// 先按比例计算两侧的提取数量，另一侧再按提取后的储备和交换费用换成输出侧代币。
+    /// 用户实际到账数量低于 `min_out` 时返回 SlippageExceeded；
+    /// 销毁全部 LP 时提取后池中没有流动性可供交换，返回 EmptyPool。
+    pub fn quote_withdraw_single(
+        &self,
+        is_x: bool,
+        amount: u64,
+        min_out: u64,
+    ) -> Result<WithdrawSingleQuote, ProgramError> {
+        if amount >= self.lp_supply {
+            return Err(AmmError::EmptyPool.into());
+        }
+
+        // ============ 按比例提取 ============
+        // 与 Withdraw 共用同一计算路径，滑点只检查最终的单边输出
+        let proportional = self.quote_withdraw(amount, 0, 0)?;
+        let (withdrawn_out, swap_in) = match is_x {
+            true => (proportional.vault_x, proportional.vault_y),
+            false => (proportional.vault_y, proportional.vault_x),
+        };
+
+        // ============ 隐含交换 ============
+        // 另一侧代币按提取后的储备换成输出侧代币，交换方向与输出方向相反
+        let (swap_out, fee) = match swap_in {
+            0 => (0, 0),
+            _ => {
+                let swap_result = compute_swap(
+                    proportional.reserve_x,
+                    proportional.reserve_y,
+                    self.lp_supply - amount,
+                    self.fee,
+                    !is_x,
+                    swap_in,
+                    1,
+                )?;
+                (swap_result.withdraw, swap_result.fee)
+            }
+        };
+        let protocol_fee = (fee as u128 * self.protocol_fee_share as u128 / 10_000) as u64;
+
+        // 金库转出按比例提取和交换换出的总和，扣除转账手续费后才是用户实际收到的数量
+        let vault_out = withdrawn_out
+            .checked_add(swap_out)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if vault_out == 0 {
+            return Err(AmmError::ZeroAmount.into());
+        }
+        let (transfer_fee_out, _) = self.transfer_fees(is_x);
+        let amount_out = transfer_fee_out.post_fee_amount(vault_out)?;
+        if amount_out < min_out {
+            return Err(AmmError::SlippageExceeded.into());
+        }
+
+        // 换入的代币从未离开金库：另一侧储备仅扣除协议费用，输出侧减少金库转出的全部数量
+        let (reserve_in, reserve_out) = match is_x {
+            true => (self.reserve_y, self.reserve_x),
+            false => (self.reserve_x, self.reserve_y),
+        };
+        let new_reserve_in = reserve_in
+            .checked_sub(protocol_fee)
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        let new_reserve_out = reserve_out
+            .checked_sub(vault_out)
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        let (vault_x, vault_y, amount_x, amount_y, reserve_x, reserve_y) = match is_x {
+            true => (vault_out, 0, amount_out, 0, new_reserve_out, new_reserve_in),
+            false => (0, vault_out, 0, amount_out, new_reserve_in, new_reserve_out),
+        };
+
+        Ok(WithdrawSingleQuote {
+            withdraw: WithdrawQuote {
+                vault_x,
+                vault_y,
+                amount_x,
+                amount_y,
+                amount_lp: amount,
+                reserve_x,
+                reserve_y,
+            },
+            swap_in,
+            swap_out,
+            fee,
+            protocol_fee,
+        })
+    }
+}
+
+/// ========== 池账户 ==========
+///
+/// 一个池的 Config、LP mint、代币 mint、金库和 Token 程序账户（不含用户账户）。
+/// 所有读取池状态的指令（交换、存入、提取、Quote、协议费用提取以及 RouteSwap 的每一跳）
+/// 共用同一套验证、储备读取和金库转出逻辑。
+#[derive(Clone, Copy)]
+pub struct PoolAccounts<'a> {
+    /// AMM 配置账户（包含池参数，是金库的权限方）
+    pub config: &'a AccountView,
+    /// LP 代币的 mint 账户（提供曲线所需的 LP 供应量）
+    pub mint_lp: &'a AccountView,
+    /// 代币 X 的 mint 账户
+    pub mint_x: &'a AccountView,
+    /// 代币 Y 的 mint 账户
+    pub mint_y: &'a AccountView,
+    /// 代币 X 的金库账户
+    pub vault_x: &'a AccountView,
+    /// 代币 Y 的金库账户
+    pub vault_y: &'a AccountView,
+    /// 代币 X 所属的 Token 程序
+    pub token_program_x: &'a AccountView,
+    /// 代币 Y 所属的 Token 程序
+    pub token_program_y: &'a AccountView,
+}
+
+impl<'a> TryFrom<&'a [AccountView]> for PoolAccounts<'a> {
+    type Error = ProgramError;
+
+    /// 按 config、mint_lp、mint_x、mint_y、vault_x、vault_y、token_program_x、token_program_y
+    /// 的顺序提取并验证池账户
+    fn try_from(accounts: &'a [AccountView]) -> Result<Self, Self::Error> {
+        let [config, mint_lp, mint_x, mint_y, vault_x, vault_y, token_program_x, token_program_y] =
+            accounts
+        else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        let pool_accounts = Self {
+            config,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+        };
+        pool_accounts.validate()?;
+
+        Ok(pool_accounts)
+    }
+}
+
+impl<'a> PoolAccounts<'a> {
+    /// 池账户数量
+    pub const LEN: usize = 8;
+
+    /// 验证 Config、LP mint、代币 mint、Token 程序和金库都属于同一个池
+    pub fn validate(&self) -> ProgramResult {
+        let Self {
+            config,
+            mint_lp,
+            mint_x,
+            mint_y,
+            vault_x,
+            vault_y,
+            token_program_x,
+            token_program_y,
+        } = *self;
+
+        // ============ Config 账户验证 ============
+        // 验证 Config 账户的大小和所有权
+        if config.data_len() != Config::LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        if !config.owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        let cfg = Config::load(config)?;
+
+        // ============ LP Mint 账户验证 ============
+        // 报价依赖 LP 供应量，必须确保 mint_lp 是本池的 LP mint PDA
+        if mint_lp.data_len() != Mint::LEN || !mint_lp.owned_by(&TOKEN_PROGRAM_ID) {
+            return Err(AmmError::InvalidMintLp.into());
+        }
+        let derived_mint_lp = Address::create_program_address(
+            &[b"mint_lp", config.address().as_ref(), &cfg.lp_bump()],
+            &crate::ID,
+        )
+        .map_err(|_| AmmError::InvalidMintLp)?;
+        if derived_mint_lp != *mint_lp.address() {
+            return Err(AmmError::InvalidMintLp.into());
+        }
+
+        // ============ Token Program 验证 ============
+        // 必须与初始化时记录在 Config 中的 Token 程序一致
+        if token_program_x.address() != cfg.token_program_x()
+            || token_program_y.address() != cfg.token_program_y()
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        // ============ 代币 X / Y mint 验证 ============
+        // TransferChecked 需要 mint 账户，必须与 Config 中的 mint 一致
+        if mint_x.address() != cfg.mint_x() || mint_y.address() != cfg.mint_y() {
+            return Err(AmmError::InvalidMint.into());
+        }
+
+        // ============ 金库账户格式和所有权验证 ============
+        // 验证金库都由对应的 Token 程序拥有且格式正确
+        check_token_account(vault_x, token_program_x.address())?;
+        check_token_account(vault_y, token_program_y.address())?;
+
+        // ============ 金库 PDA 验证 ============
+        // 验证金库地址是否为 Config 持有的关联代币账户
+        // 验证 vault_x 是否为正确的 PDA
+        let (derived_vault_x, _) = Address::find_program_address(
+            &[
+                config.address().as_ref(),
+                token_program_x.address().as_ref(),
+                cfg.mint_x().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        if derived_vault_x != *vault_x.address() {
+            return Err(AmmError::InvalidVault.into());
+        }
+
+        // 验证 vault_y 是否为正确的 PDA
+        let (derived_vault_y, _) = Address::find_program_address(
+            &[
+                config.address().as_ref(),
+                token_program_y.address().as_ref(),
+                cfg.mint_y().as_ref(),
+            ],
+            &pinocchio_associated_token_account::ID,
+        );
+        if derived_vault_y != *vault_y.address() {
+            return Err(AmmError::InvalidVault.into());
+        }
+
+        // ============ 金库所有者验证 ============
+        // 金库的代币账户所有者必须是 Config PDA
+        {
+            let vault_x_account = unsafe { token_account_unchecked(vault_x) };
+            let vault_y_account = unsafe { token_account_unchecked(vault_y) };
+            if vault_x_account.owner() != config.address()
+                || vault_y_account.owner() != config.address()
+            {
+                return Err(AmmError::InvalidVaultOwner.into());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 读取计算报价所需的池状态
+    ///
+    /// # Safety
+    /// 账户已在 `validate` 中验证；调用期间不能存在金库或 mint 的可变借用。
+    pub unsafe fn pool(&self, config: &Config) -> Result<Pool, ProgramError> {
+        unsafe {
+            Pool::load(
+                config,
+                self.vault_x,
+                self.vault_y,
+                self.mint_lp,
+                self.mint_x,
+                self.mint_y,
+            )
+        }
+    }
+
+    /// 将金库的 `amount_out` 个输出代币转给 `output_account`（Config PDA 签名）
+    ///
+    /// `is_x` 为 true 表示输入 X、输出 Y。接收账户可以是另一个池的金库（RouteSwap 的中间跳）。
+    pub fn transfer_out(
+        &self,
+        is_x: bool,
+        output_account: &AccountView,
+        amount_out: u64,
+    ) -> ProgramResult {
+        // 获取 Config PDA 的数据（用于生成签名）
+        let cfg = Config::load(self.config)?;
+
+        let config_seeds = cfg.signer_seeds();
+        let signer = [Signer::from(&config_seeds)];
+
+        let (vault_out, mint_out, token_program_out) = match is_x {
+            true => (self.vault_y, self.mint_y, self.token_program_y),
+            false => (self.vault_x, self.mint_x, self.token_program_x),
+        };
+        TransferChecked {
+            from: vault_out,
+            mint: mint_out,
+            to: output_account,
+            authority: self.config,
+            amount: amount_out,
+            decimals: unsafe { mint_unchecked(mint_out) }.decimals(),
+            token_program: token_program_out.address(),
+        }
+        .invoke_signed(&signer)
+    }
+}
+
+/// ========== 交换计算 ==========
+///
+/// 以净储备、LP 供应量和费率初始化常数乘积曲线，计算输入 `amount_in` 后的交换结果。
+/// `is_x` 为 true 表示输入 X、输出 Y。结果中任一数量为 0 时返回 ZeroAmount。
+pub fn compute_swap(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    fee: u16,
+    is_x: bool,
+    amount_in: u64,
+    min_out: u64,
+) -> Result<SwapResult, ProgramError> {
+    let mut curve = ConstantProduct::init(
+        reserve_x, // X 净储备
+        reserve_y, // Y 净储备
+        lp_supply, // LP 代币供应量
+        fee,       // 交换费用（以 bps 计）
+        None,      // 自定义精度（无）
+    )
+    .map_err(AmmError::from)?;
+
+    // 根据 is_x 确定用户输入的是 X 还是 Y
+    let p = match is_x {
+        true => LiquidityPair::X,  // 用户输入 X，输出 Y
+        false => LiquidityPair::Y, // 用户输入 Y，输出 X
+    };
+
+    let swap_result = curve.swap(p, amount_in, min_out).map_err(AmmError::from)?;
+
+    // 验证计算结果的有效性
+    if swap_result.deposit == 0 || swap_result.withdraw == 0 {
+        return Err(AmmError::ZeroAmount.into());
+    }
+
+    Ok(swap_result)
+}
+
+/// ========== 含转账手续费的转入数量 ==========
+///
+/// 金库需要实际收到 `vault_amount` 时用户需要转出的数量。按转账手续费反推的数量因取整超过
+/// `max`、而转出 `max` 本身已足够时取 `max`，避免按代币数量存入时出现虚假的滑点错误。
+fn transfer_in_amount(
+    transfer_fee: TransferFee,
+    vault_amount: u64,
+    max: u64,
+) -> Result<u64, ProgramError> {
+    let amount = transfer_fee.pre_fee_amount(vault_amount)?;
+    if amount > max && transfer_fee.post_fee_amount(max)? >= vault_amount {
+        return Ok(max);
+    }
+    Ok(amount)
+}
+
+/// ========== 按 LP 比例计算代币数量 ==========
+///
+/// 返回 `reserve * amount / lp_supply`，在 u128 中计算以避免溢出。
+/// 存入时向上取整、提取时向下取整，取整始终有利于池；LP 供应量为 0 时返回 CurveError。
+/// 比例计算与 LP mint 的精度无关。
+pub fn proportional_amount(
+    reserve: u64,
+    amount: u64,
+    lp_supply: u64,
+    round_up: bool,
+) -> Result<u64, ProgramError> {
+    if lp_supply == 0 {
+        return Err(AmmError::CurveError.into());
+    }
+    let numerator = reserve as u128 * amount as u128;
+    let result = match round_up {
+        true => numerator.div_ceil(lp_supply as u128),
+        false => numerator / lp_supply as u128,
+    };
+    u64::try_from(result).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// ========== 精确输出交换计算 ==========
+///
+/// 计算从池中换出恰好 `amount_out` 个输出代币所需的最少输入数量，取整均向有利于池的方向。
+/// 先按常数乘积公式反推输入（向上取整）并加回交换费用（向上取整），
+/// 再以 [`compute_swap`] 的结果为准修正为输出足够的最小输入，保证与精确输入交换使用同一条计算路径。
+/// 返回结果中 `deposit` 为所需输入，`withdraw` 恰好等于 `amount_out`。
+pub fn compute_swap_exact_out(
+    reserve_x: u64,
+    reserve_y: u64,
+    lp_supply: u64,
+    fee: u16,
+    is_x: bool,
+    amount_out: u64,
+) -> Result<SwapResult, ProgramError> {
+    if amount_out == 0 {
+        return Err(AmmError::ZeroAmount.into());
+    }
+
+    let (reserve_in, reserve_out) = match is_x {
+        true => (reserve_x, reserve_y),
+        false => (reserve_y, reserve_x),
+    };
+    // 池中输出代币不足
+    if amount_out >= reserve_out {
+        return Err(AmmError::CurveError.into());
+    }
+
+    // 扣除手续费后需要进入曲线的输入：reserve_in * amount_out / (reserve_out - amount_out)
+    let net_in =
+        (reserve_in as u128 * amount_out as u128).div_ceil((reserve_out - amount_out) as u128);
+    // 加回交换费用：net_in * 10000 / (10000 - fee)
+    let gross_in = net_in
+        .checked_mul(10_000)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        .div_ceil(10_000 - fee as u128);
+    let amount_in = u64::try_from(gross_in).map_err(|_| AmmError::CurveError)?;
+
+    // 以精确输入的计算结果为准：输入为 amount_in 时输出是否足够
+    let enough = |amount_in: u64| {
+        compute_swap(reserve_x, reserve_y, lp_supply, fee, is_x, amount_in, 1)
+            .is_ok_and(|swap_result| swap_result.withdraw >= amount_out)
+    };
+
+    // 从估算值出发按倍增的步长向一侧试探，得到区间 (short, sufficient]：
+    // short 的输出不足，sufficient 的输出足够。试探次数只与估算误差的对数成正比
+    let mut step = 1u64;
+    let (mut short, mut sufficient) = match enough(amount_in) {
+        true => {
+            let mut sufficient = amount_in;
+            loop {
+                let candidate = sufficient.saturating_sub(step);
+                if candidate == 0 || !enough(candidate) {
+                    break (candidate, sufficient);
+                }
+                sufficient = candidate;
+                step = step.saturating_mul(2);
+            }
+        }
+        false => {
+            let mut short = amount_in;
+            loop {
+                let candidate = short.checked_add(step).ok_or(AmmError::CurveError)?;
+                if enough(candidate) {
+                    break (short, candidate);
+                }
+                short = candidate;
+                step = step.checked_mul(2).ok_or(AmmError::CurveError)?;
+            }
+        }
+    };
+
+    // 二分查找输出足够的最小输入
+    while sufficient - short > 1 {
+        let mid = short + (sufficient - short) / 2;
+        match enough(mid) {
+            true => sufficient = mid,
+            false => short = mid,
+        }
+    }
+
+    let swap_result = compute_swap(reserve_x, reserve_y, lp_supply, fee, is_x, sufficient, 1)?;
+    // 多出的输出留在池中
+    Ok(SwapResult {
+        deposit: swap_result.deposit,
+        withdraw: amount_out,
+        fee: swap_result.fee,
+    })
+}
+
+/// ========== 闪电交换不变量检查 ==========
+///
+/// 闪电交换先从金库转出 `out_x` / `out_y`，回调结束后按净储备 `new_reserve_x` / `new_reserve_y`
+/// 验证常数乘积不变量（参考 Uniswap V2）：每一侧转入的数量为余额超过 `储备 - 转出` 的部分，
+/// 按交换费用扣除（向上取整）后的储备乘积不能小于交换前的 k。
+/// 成功时返回（代币 X 交换费用，代币 Y 交换费用），不满足时返回 FlashLoanNotRepaid。
+pub fn check_flash_swap(
+    reserve_x: u64,
+    reserve_y: u64,
+    out_x: u64,
+    out_y: u64,
+    new_reserve_x: u64,
+    new_reserve_y: u64,
+    fee: u16,
+) -> Result<(u64, u64), ProgramError> {
+    // 每一侧转入的数量及其交换费用
+    let side_fee = |reserve: u64, out: u64, new_reserve: u64| -> Result<u64, ProgramError> {
+        let remaining = reserve
+            .checked_sub(out)
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        let paid = new_reserve.saturating_sub(remaining) as u128;
+        Ok((paid * fee as u128).div_ceil(10_000) as u64)
+    };
+    let fee_x = side_fee(reserve_x, out_x, new_reserve_x)?;
+    let fee_y = side_fee(reserve_y, out_y, new_reserve_y)?;
+
+    // 扣除交换费用后的 k 不能减少（两个 u64 的乘积不会溢出 u128）
+    let k_before = reserve_x as u128 * reserve_y as u128;
+    let k_after =
+        new_reserve_x.saturating_sub(fee_x) as u128 * new_reserve_y.saturating_sub(fee_y) as u128;
+    if k_after < k_before {
+        return Err(AmmError::FlashLoanNotRepaid.into());
+    }
+
+    Ok((fee_x, fee_y))
+}
+
+/// 计算单边存入精度时使用的定点小数位数
+const ZAP_PRECISION_BITS: u32 = 32;
+
+/// ========== 单边存入的交换数量 ==========
+///
+/// 输入侧储备为 R、金库收到的输入为 v、扣费比例为 a = (10000 - fee) / 10000 时，
+/// 使交换后剩余输入与换得的输出恰好符合交换后储备比例的交换数量为
+/// s = (sqrt(R² (1 + a)² + 4 a R v) - R (1 + a)) / (2a)。
+/// 为避免 R² 溢出，分子有理化后写成 s = 2v / (sqrt((1 + a)² + 4 a v / R) + (1 + a))，
+/// 其中 v / R 以 [`ZAP_PRECISION_BITS`] 位定点数计算。结果向下取整，零头留在池中。
+pub fn zap_swap_amount(reserve_in: u64, amount_in: u64, fee: u16) -> Result<u64, ProgramError> {
+    if reserve_in == 0 {
+        return Err(AmmError::EmptyPool.into());
+    }
+
+    let one = 1u128 << ZAP_PRECISION_BITS;
+    let a = (10_000 - fee as u128) * one / 10_000;
+    let one_plus_a = one + a;
+
+    // 4 a v / R 与 (1 + a)² 均以 2 * ZAP_PRECISION_BITS 位定点数表示
+    let discriminant = (amount_in as u128 * 4 * a / reserve_in as u128)
+        .checked_mul(one)
+        .and_then(|ratio| ratio.checked_add(one_plus_a * one_plus_a))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let denominator = isqrt(discriminant) + one_plus_a;
+
+    let swap_amount = 2 * amount_in as u128 * one / denominator;
+    u64::try_from(swap_amount).map_err(|_| ProgramError::ArithmeticOverflow)
+}
+
+/// 整数平方根（向下取整）
+fn isqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // 牛顿迭代：从不小于真实值的初值开始单调递减
+    let mut x = 1u128 << (128 - n.leading_zeros()).div_ceil(2);
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pinocchio::account::{RuntimeAccount, NOT_BORROWED};
+    use pinocchio_token::state::TokenAccount;
+
+    /// 常数乘积不变量：交换后 k 不能减少（手续费留在池中，k 只会增加）
+    fn assert_k_not_decreased(reserve_x: u64, reserve_y: u64, is_x: bool, result: &SwapResult) {
+        let k_before = reserve_x as u128 * reserve_y as u128;
+        let (x_after, y_after) = match is_x {
+            true => (reserve_x + result.deposit, reserve_y - result.withdraw),
+            false => (reserve_x - result.withdraw, reserve_y + result.deposit),
+        };
+        assert!(x_after as u128 * y_after as u128 >= k_before);
+    }
+
+    /// 测试用账户：与运行时输入的内存布局一致，`RuntimeAccount` 头部之后紧跟账户数据
+    struct TestAccount(Vec<u64>);
+
+    impl TestAccount {
+        fn new(address: Address, owner: Address, data: &[u8]) -> Self {
+            let header = core::mem::size_of::<RuntimeAccount>();
+            let mut buffer = vec![0u64; (header + data.len()).div_ceil(8)];
+            let raw = buffer.as_mut_ptr() as *mut RuntimeAccount;
+            unsafe {
+                raw.write(RuntimeAccount {
+                    borrow_state: NOT_BORROWED,
+                    is_signer: 0,
+                    is_writable: 1,
+                    executable: 0,
+                    resize_delta: 0,
+                    address,
+                    owner,
+                    lamports: 1,
+                    data_len: data.len() as u64,
+                });
+                core::ptr::copy_nonoverlapping(
+                    data.as_ptr(),
+                    (raw as *mut u8).add(header),
+                    data.len(),
+                );
+            }
+            Self(buffer)
+        }
+
+        fn view(&mut self) -> AccountView {
+            unsafe { AccountView::new_unchecked(self.0.as_mut_ptr() as *mut RuntimeAccount) }
+        }
+    }
+
+    /// SPL Token mint 数据：供应量位于偏移 36，精度位于偏移 44
+    fn mint_data(supply: u64) -> [u8; Mint::LEN] {
+        let mut data = [0u8; Mint::LEN];
+        data[36..44].copy_from_slice(&supply.to_le_bytes());
+        data[44] = 6;
+        data[45] = 1;
+        data
+    }
+
+    /// SPL Token 代币账户数据：mint、所有者和余额依次位于开头
+    fn token_account_data(mint: &Address, owner: &Address, amount: u64) -> Vec<u8> {
+        let mut data = vec![0u8; TokenAccount::LEN];
+        data[..32].copy_from_slice(mint.as_ref());
+        data[32..64].copy_from_slice(owner.as_ref());
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        data[108] = 1;
+        data
+    }
+
+    /// 按 PDA 推导构造一个真实的池：X 储备与 LP 供应量不同（典型的 sqrt(x*y) 初始 LP）
+    struct TestPool {
+        config: TestAccount,
+        mint_lp: TestAccount,
+        mint_x: TestAccount,
+        mint_y: TestAccount,
+        vault_x: TestAccount,
+        vault_y: TestAccount,
+        token_program: TestAccount,
+    }
+
+    impl TestPool {
+        fn new(reserve_x: u64, reserve_y: u64, lp_supply: u64) -> Self {
+            let (mint_x, mint_y) = Config::sort_mints(
+                Address::new_from_array([7; 32]),
+                Address::new_from_array([9; 32]),
+            );
+            let (config, config_bump) = Config::find_address(1, mint_x, mint_y);
+            let (mint_lp, lp_bump) = Config::find_mint_lp_address(&config);
+            let vault = |mint: &Address| {
+                Address::find_program_address(
+                    &[config.as_ref(), TOKEN_PROGRAM_ID.as_ref(), mint.as_ref()],
+                    &pinocchio_associated_token_account::ID,
+                )
+                .0
+            };
+
+            let mut config_data = [0u8; Config::LEN];
+            unsafe { Config::from_bytes_unchecked_mut(&mut config_data) }
+                .set_inner(
+                    1,
+                    Address::default(),
+                    mint_x,
+                    mint_y,
+                    TOKEN_PROGRAM_ID,
+                    TOKEN_PROGRAM_ID,
+                    30,
+                    [config_bump],
+                    [lp_bump],
+                    6,
+                )
+                .unwrap();
+
+            Self {
+                config: TestAccount::new(config, crate::ID, &config_data),
+                mint_lp: TestAccount::new(mint_lp, TOKEN_PROGRAM_ID, &mint_data(lp_supply)),
+                mint_x: TestAccount::new(mint_x, TOKEN_PROGRAM_ID, &mint_data(0)),
+                mint_y: TestAccount::new(mint_y, TOKEN_PROGRAM_ID, &mint_data(0)),
+                vault_x: TestAccount::new(
+                    vault(&mint_x),
+                    TOKEN_PROGRAM_ID,
+                    &token_account_data(&mint_x, &config, reserve_x),
+                ),
+                vault_y: TestAccount::new(
+                    vault(&mint_y),
+                    TOKEN_PROGRAM_ID,
+                    &token_account_data(&mint_y, &config, reserve_y),
+                ),
+                token_program: TestAccount::new(TOKEN_PROGRAM_ID, Address::default(), &[]),
+            }
+        }
+    }
+
+    #[test]
+    fn pool_accounts_reject_foreign_mint_lp() {
+        let mut test_pool = TestPool::new(1_000_000, 4_000_000, 2_000_000);
+        // 格式正确、由 Token 程序拥有，但不是本池 LP mint PDA 的 mint
+        let mut foreign = TestAccount::new(
+            Address::new_from_array([3; 32]),
+            TOKEN_PROGRAM_ID,
+            &mint_data(1),
+        );
+        let (config, mint_x, mint_y) = (
+            test_pool.config.view(),
+            test_pool.mint_x.view(),
+            test_pool.mint_y.view(),
+        );
+        let (vault_x, vault_y, token_program) = (
+            test_pool.vault_x.view(),
+            test_pool.vault_y.view(),
+            test_pool.token_program.view(),
+        );
+
+        // 其他池的 mint 以及本池的代币 mint 都不能冒充 LP mint
+        for mint_lp in [foreign.view(), test_pool.mint_x.view()] {
+            let pool_accounts = PoolAccounts {
+                config: &config,
+                mint_lp: &mint_lp,
+                mint_x: &mint_x,
+                mint_y: &mint_y,
+                vault_x: &vault_x,
+                vault_y: &vault_y,
+                token_program_x: &token_program,
+                token_program_y: &token_program,
+            };
+            assert_eq!(
+                pool_accounts.validate().unwrap_err(),
+                ProgramError::from(AmmError::InvalidMintLp)
+            );
+        }
+    }
+
+    #[test]
+    fn pool_load_uses_lp_supply_not_x_reserve() {
+        for (reserve_x, reserve_y, lp_supply) in [
+            (1_000_000, 4_000_000, 2_000_000),
+            (9_000_000_000, 1_000_000, 3_000_000),
+        ] {
+            let mut test_pool = TestPool::new(reserve_x, reserve_y, lp_supply);
+            let (config, mint_lp) = (test_pool.config.view(), test_pool.mint_lp.view());
+            let (mint_x, mint_y) = (test_pool.mint_x.view(), test_pool.mint_y.view());
+            let (vault_x, vault_y, token_program) = (
+                test_pool.vault_x.view(),
+                test_pool.vault_y.view(),
+                test_pool.token_program.view(),
+            );
+            let pool_accounts = PoolAccounts {
+                config: &config,
+                mint_lp: &mint_lp,
+                mint_x: &mint_x,
+                mint_y: &mint_y,
+                vault_x: &vault_x,
+                vault_y: &vault_y,
+                token_program_x: &token_program,
+                token_program_y: &token_program,
+            };
+            pool_accounts.validate().unwrap();
+
+            let cfg = Config::load(&config).unwrap();
+            let loaded = unsafe { pool_accounts.pool(&cfg) }.unwrap();
+            assert_eq!(loaded, pool(reserve_x, reserve_y, lp_supply, 30));
+
+            // 报价使用 LP mint 的供应量作为 L，而不是 X 储备
+            for (is_x, amount_in) in [(true, 9_000_000), (false, 1_000)] {
+                let quote = loaded.quote_swap(is_x, amount_in, 1).unwrap();
+                let expected =
+                    compute_swap(reserve_x, reserve_y, lp_supply, 30, is_x, amount_in, 1).unwrap();
+                assert_eq!(quote.amount_out, expected.withdraw);
+                assert!(quote.amount_out > 0);
+                assert_k_not_decreased(reserve_x, reserve_y, is_x, &expected);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_pays_exact_amount_and_rounds_for_pool() {
+        let (reserve_x, reserve_y, lp_supply) = (1_000_000, 4_000_000, 2_000_000);
+
+        for (is_x, amount_out) in [(true, 12_345), (false, 777), (true, 1), (false, 999_000)] {
+            let result =
+                compute_swap_exact_out(reserve_x, reserve_y, lp_supply, 30, is_x, amount_out)
+                    .unwrap();
+            assert_eq!(result.withdraw, amount_out);
+
+            // 用算出的输入做精确输入交换，输出不少于要求的数量
+            let exact_in =
+                compute_swap(reserve_x, reserve_y, lp_supply, 30, is_x, result.deposit, 1).unwrap();
+            assert!(exact_in.withdraw >= amount_out);
+            assert_k_not_decreased(reserve_x, reserve_y, is_x, &result);
+        }
+    }
+
+    #[test]
+    fn exact_out_finds_minimal_input_for_large_reserves() {
+        // 大储备和非零费率下同样找到输出足够的最小输入
+        let (reserve_x, reserve_y, lp_supply) = (
+            1_000_000_000_000_000,
+            3_000_000_000_000_000,
+            1_700_000_000_000_000,
+        );
+
+        for fee in [30, 100, 1_000] {
+            for (is_x, amount_out) in [
+                (true, 1),
+                (true, 123_456_789_012),
+                (true, 2_000_000_000_000_000),
+                (false, 900_000_000_000_000),
+            ] {
+                let swap = |amount_in| {
+                    compute_swap(reserve_x, reserve_y, lp_supply, fee, is_x, amount_in, 1)
+                };
+
+                let result =
+                    compute_swap_exact_out(reserve_x, reserve_y, lp_supply, fee, is_x, amount_out)
+                        .unwrap();
+                assert_eq!(result.withdraw, amount_out);
+
+                // 算出的输入足够，少 1 个则不足
+                assert!(swap(result.deposit).unwrap().withdraw >= amount_out);
+                assert!(!swap(result.deposit - 1).is_ok_and(|short| short.withdraw >= amount_out));
+            }
+        }
+    }
+
+    #[test]
+    fn exact_out_rejects_draining_the_pool() {
+        let result = compute_swap_exact_out(1_000_000, 4_000_000, 2_000_000, 30, true, 4_000_000);
+        assert_eq!(result.unwrap_err(), AmmError::CurveError.into());
+
+        let result = compute_swap_exact_out(1_000_000, 4_000_000, 2_000_000, 30, true, 0);
+        assert_eq!(result.unwrap_err(), AmmError::ZeroAmount.into());
+    }
+
+    fn pool(reserve_x: u64, reserve_y: u64, lp_supply: u64, fee: u16) -> Pool {
+        Pool {
+            reserve_x,
+            reserve_y,
+            lp_supply,
+            fee,
+            protocol_fee_share: 0,
+            transfer_fee_x: TransferFee::ZERO,
+            transfer_fee_y: TransferFee::ZERO,
+        }
+    }
+
+    #[test]
+    fn deposit_and_withdraw_are_proportional_for_zero_decimal_pool() {
+        // 0 位精度的代币对（Initialize 默认 lp_decimals = 0），储备和 LP 都很小
+        let pool = pool(1_000, 4_000, 2_000, 30);
+
+        // 销毁 10% 的 LP 只取回 10% 的储备，而不是全部储备
+        let quote = pool.quote_withdraw(200, 0, 0).unwrap();
+        assert_eq!((quote.vault_x, quote.vault_y), (100, 400));
+        assert_eq!((quote.reserve_x, quote.reserve_y), (900, 3_600));
+
+        // 提取向下取整：1 个 LP 对应 0.5 X，取回 0 X
+        let quote = pool.quote_withdraw(1, 0, 0).unwrap();
+        assert_eq!((quote.vault_x, quote.vault_y), (0, 2));
+
+        // 存入按比例收取代币，向上取整：1 个 LP 需要 1 X（而不是 0 X）
+        let quote = pool.quote_deposit(200, u64::MAX, u64::MAX).unwrap();
+        assert_eq!((quote.amount_x, quote.amount_y), (100, 400));
+        let quote = pool.quote_deposit(1, u64::MAX, u64::MAX).unwrap();
+        assert_eq!((quote.amount_x, quote.amount_y), (1, 2));
+    }
+
+    #[test]
+    fn bootstrap_deposit_derives_lp_from_deposited_amounts() {
+        let pool = pool(0, 0, 0, 30);
+
+        // LP 总量为 sqrt(x * y)，amount 只是下限
+        let quote = pool.quote_deposit(1_001, 1_000_000, 4_000_000).unwrap();
+        assert!(quote.is_bootstrap);
+        assert_eq!(quote.amount_lp, 2_000_000 - MINIMUM_LIQUIDITY);
+        assert_eq!((quote.amount_x, quote.amount_y), (1_000_000, 4_000_000));
+
+        // 首个存入者不能用极少的代币铸造任意数量的 LP
+        let result = pool.quote_deposit(u64::MAX, 1, 1);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+        let result = pool.quote_deposit(1_000_000, 1_000, 1_000);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+
+        // 几何平均数不足以覆盖锁定的最小流动性
+        let result = pool.quote_deposit(1, 1_000, 1_000);
+        assert_eq!(
+            result.unwrap_err(),
+            AmmError::InsufficientInitialLiquidity.into()
+        );
+    }
+
+    #[test]
+    fn deposit_rejects_zero_amount_side() {
+        // 一侧净储备为 0 时按比例所需的数量为 0，不能免费铸造 LP
+        let result = pool(0, 4_000, 2_000, 30).quote_deposit(200, u64::MAX, u64::MAX);
+        assert_eq!(result.unwrap_err(), AmmError::ZeroAmount.into());
+        let result = pool(1_000, 0, 2_000, 30).quote_deposit(1, u64::MAX, u64::MAX);
+        assert_eq!(result.unwrap_err(), AmmError::ZeroAmount.into());
+    }
+
+    #[test]
+    fn deposit_and_withdraw_are_proportional_for_nine_decimal_pool() {
+        // 9 位精度的代币对：100 万 X、400 万 Y
+        let (reserve_x, reserve_y, lp_supply) = (
+            1_000_000_000_000_000,
+            4_000_000_000_000_000,
+            2_000_000_000_000_000,
+        );
+        let pool = pool(reserve_x, reserve_y, lp_supply, 30);
+
+        let quote = pool.quote_withdraw(lp_supply / 10, 0, 0).unwrap();
+        assert_eq!(
+            (quote.vault_x, quote.vault_y),
+            (reserve_x / 10, reserve_y / 10)
+        );
+
+        let quote = pool
+            .quote_deposit(lp_supply / 10, u64::MAX, u64::MAX)
+            .unwrap();
+        assert_eq!(
+            (quote.amount_x, quote.amount_y),
+            (reserve_x / 10, reserve_y / 10)
+        );
+
+        // 取整始终有利于池：同一 LP 数量的存入成本不低于提取所得
+        for amount in [1, 3, 12_345_678_901, lp_supply - 1] {
+            let deposit = pool.quote_deposit(amount, u64::MAX, u64::MAX).unwrap();
+            let withdraw = pool.quote_withdraw(amount, 0, 0).unwrap();
+            assert!(deposit.amount_x >= withdraw.vault_x);
+            assert!(deposit.amount_y >= withdraw.vault_y);
+            assert!(deposit.amount_x - withdraw.vault_x <= 1);
+            assert!(deposit.amount_y - withdraw.vault_y <= 1);
+        }
+
+        // 销毁全部 LP 取回全部储备，超过供应量则失败
+        let quote = pool.quote_withdraw(lp_supply, 0, 0).unwrap();
+        assert_eq!((quote.reserve_x, quote.reserve_y), (0, 0));
+        let result = pool.quote_withdraw(lp_supply + 1, 0, 0);
+        assert_eq!(result.unwrap_err(), AmmError::CurveError.into());
+    }
+
+    #[test]
+    fn zap_swap_amount_balances_remaining_input() {
+        // 无手续费时存入 3R：交换 R 后剩余 2R，与交换后储备 (2R, Y/2) 比例一致
+        let swap_in = zap_swap_amount(1_000_000, 3_000_000, 0).unwrap();
+        assert!(swap_in.abs_diff(1_000_000) <= 1);
+
+        // 有手续费时需要交换的数量略多
+        assert!(zap_swap_amount(1_000_000, 3_000_000, 30).unwrap() > swap_in);
+    }
+
+    #[test]
+    fn deposit_by_amounts_takes_balanced_subset() {
+        // 储备比例 1:4，用户给出 1:8，只存入 1:4 的部分，多余的 Y 留在用户账户
+        let quote = pool(1_000_000, 4_000_000, 2_000_000, 30)
+            .quote_deposit_by_amounts(100_000, 800_000, 1)
+            .unwrap();
+        assert_eq!(quote.amount_lp, 200_000);
+        assert_eq!((quote.amount_x, quote.amount_y), (100_000, 400_000));
+        assert_eq!((quote.reserve_x, quote.reserve_y), (1_100_000, 4_400_000));
+        assert!(!quote.is_bootstrap);
+
+        let result = pool(1_000_000, 4_000_000, 2_000_000, 30)
+            .quote_deposit_by_amounts(100_000, 800_000, 200_001);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+    }
+
+    #[test]
+    fn deposit_by_amounts_with_transfer_fees() {
+        // X 收取 1% 转账手续费，Y 收取 1% 且单笔上限 50
+        let mut pool = pool(1_000_000, 4_000_000, 2_000_000, 30);
+        pool.transfer_fee_x = TransferFee {
+            maximum_fee: u64::MAX,
+            basis_points: 100,
+        };
+        pool.transfer_fee_y = TransferFee {
+            maximum_fee: 50,
+            basis_points: 100,
+        };
+
+        // 金库收到 99_000 X，按 X 一侧铸造 198_000 LP，需要金库收到 396_000 Y
+        let quote = pool.quote_deposit_by_amounts(100_000, 800_000, 1).unwrap();
+        assert_eq!(quote.amount_lp, 198_000);
+        assert_eq!((quote.amount_x, quote.amount_y), (100_000, 396_050));
+        assert_eq!((quote.reserve_x, quote.reserve_y), (1_099_000, 4_396_000));
+
+        // 任意给出的数量都不会因为手续费反推的取整而出现虚假的滑点错误
+        for amount_x in [1_001, 10_001, 33_333, 99_999, 100_000, 123_457] {
+            for amount_y in [4_099, 40_001, 133_333, 399_999, 400_000, 493_827] {
+                let quote = pool
+                    .quote_deposit_by_amounts(amount_x, amount_y, 1)
+                    .unwrap();
+                assert!(quote.amount_x <= amount_x && quote.amount_y <= amount_y);
+            }
+        }
+    }
+
+    #[test]
+    fn deposit_by_amounts_bootstrap_uses_geometric_mean() {
+        let quote = pool(0, 0, 0, 30)
+            .quote_deposit_by_amounts(1_000_000, 4_000_000, 1)
+            .unwrap();
+        assert!(quote.is_bootstrap);
+        assert_eq!(quote.amount_lp, 2_000_000 - MINIMUM_LIQUIDITY);
+        assert_eq!((quote.amount_x, quote.amount_y), (1_000_000, 4_000_000));
+    }
+
+    #[test]
+    fn deposit_single_mints_balanced_lp_and_keeps_output_in_pool() {
+        let (reserve_x, reserve_y, lp_supply) = (1_000_000, 4_000_000, 2_000_000);
+
+        // 无手续费时存入 3 倍储备，理想情况下 LP 供应量翻倍
+        let quote = pool(reserve_x, reserve_y, lp_supply, 0)
+            .quote_deposit_single(true, 3_000_000, 1)
+            .unwrap();
+        assert!(quote.deposit.amount_lp <= lp_supply);
+        assert!(lp_supply - quote.deposit.amount_lp <= lp_supply / 1_000);
+        assert_eq!(quote.deposit.amount_x, 3_000_000);
+        assert_eq!(quote.deposit.amount_y, 0);
+        assert_eq!(quote.deposit.reserve_x, reserve_x + 3_000_000);
+        assert_eq!(quote.deposit.reserve_y, reserve_y);
+
+        // 交换费用使铸造的 LP 减少
+        let without_fee = pool(reserve_x, reserve_y, lp_supply, 0)
+            .quote_deposit_single(false, 3_000_000, 1)
+            .unwrap();
+        let with_fee = pool(reserve_x, reserve_y, lp_supply, 30)
+            .quote_deposit_single(false, 3_000_000, 1)
+            .unwrap();
+        assert!(with_fee.fee > 0);
+        assert!(with_fee.deposit.amount_lp < without_fee.deposit.amount_lp);
+        assert_eq!(with_fee.deposit.reserve_x, reserve_x);
+    }
+
+    #[test]
+    fn deposit_single_rejects_empty_pool_and_low_lp() {
+        let result = pool(0, 0, 0, 30).quote_deposit_single(true, 1_000, 1);
+        assert_eq!(result.unwrap_err(), AmmError::EmptyPool.into());
+
+        let result =
+            pool(1_000_000, 1_000_000, 1_000_000, 30).quote_deposit_single(true, 10_000, 1_000_000);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+    }
+
+    #[test]
+    fn withdraw_single_pays_one_side_and_keeps_other_in_pool() {
+        let (reserve_x, reserve_y, lp_supply) = (1_000_000, 4_000_000, 2_000_000);
+        let pool = pool(reserve_x, reserve_y, lp_supply, 30);
+
+        // 销毁 10% 的 LP：按比例部分恰好为 10% 的储备
+        let proportional = pool.quote_withdraw(200_000, 0, 0).unwrap();
+        assert_eq!(
+            (proportional.vault_x, proportional.vault_y),
+            (100_000, 400_000)
+        );
+
+        let quote = pool.quote_withdraw_single(true, 200_000, 1).unwrap();
+        // 输出为按比例提取的 X 加上另一侧 400_000 Y 隐含交换换出的 X
+        assert_eq!(quote.swap_in, 400_000);
+        assert_eq!(quote.withdraw.vault_x, 100_000 + quote.swap_out);
+
+        // 输出多于按比例提取的部分，但少于无手续费时的等值数量
+        assert!(quote.withdraw.amount_x > proportional.amount_x);
+        assert!(quote.withdraw.amount_x < proportional.amount_x * 2);
+        assert_eq!(quote.withdraw.amount_y, 0);
+        assert_eq!(quote.swap_in, proportional.vault_y);
+        assert_eq!(quote.withdraw.reserve_y, reserve_y);
+        assert_eq!(quote.withdraw.reserve_x, reserve_x - quote.withdraw.vault_x);
+    }
+
+    #[test]
+    fn withdraw_single_rejects_low_output_and_full_burn() {
+        let pool = pool(1_000_000, 1_000_000, 1_000_000, 30);
+
+        let result = pool.quote_withdraw_single(false, 100_000, 200_000);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+
+        let result = pool.quote_withdraw_single(false, 1_000_000, 1);
+        assert_eq!(result.unwrap_err(), AmmError::EmptyPool.into());
+    }
+
+    #[test]
+    fn flash_swap_requires_fee_adjusted_k() {
+        let (reserve_x, reserve_y) = (1_000_000, 4_000_000);
+
+        // 借出 40_000 Y：不计费用需要归还 10_102 X，加上交换费用后 10_140 X 满足不变量
+        let (fee_x, fee_y) = check_flash_swap(
+            reserve_x,
+            reserve_y,
+            0,
+            40_000,
+            reserve_x + 10_140,
+            reserve_y - 40_000,
+            30,
+        )
+        .unwrap();
+        assert!(fee_x > 0);
+        assert_eq!(fee_y, 0);
+
+        // 不付交换费用的归还不满足不变量
+        let result = check_flash_swap(
+            reserve_x,
+            reserve_y,
+            0,
+            40_000,
+            reserve_x + 10_102,
+            reserve_y - 40_000,
+            30,
+        );
+        assert_eq!(result.unwrap_err(), AmmError::FlashLoanNotRepaid.into());
+
+        // 原样归还借出的 Y 也需要支付交换费用
+        let result = check_flash_swap(reserve_x, reserve_y, 0, 40_000, reserve_x, reserve_y, 30);
+        assert_eq!(result.unwrap_err(), AmmError::FlashLoanNotRepaid.into());
+    }
+
+    #[test]
+    fn swap_rejects_output_below_min() {
+        let result = compute_swap(1_000_000, 1_000_000, 1_000_000, 30, true, 10_000, 1_000_000);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+    }
+
+    #[test]
+    fn route_chains_hops_through_vault_out() {
+        // A/B 池和 B/C 池，中间代币 B 带 1% 转账手续费
+        let mut ab = pool(1_000_000, 4_000_000, 2_000_000, 30);
+        ab.transfer_fee_y = TransferFee {
+            maximum_fee: u64::MAX,
+            basis_points: 100,
+        };
+        let mut bc = pool(4_000_000, 2_000_000, 2_800_000, 30);
+        bc.transfer_fee_x = ab.transfer_fee_y;
+
+        // 第一跳用 A 换 B，只在最后一跳检查滑点
+        let first = ab.quote_swap(true, 10_000, 0).unwrap();
+        assert!(first.amount_out < first.vault_out);
+
+        // 第二跳的输入是第一跳金库转出的数量，B 的转账手续费只扣一次
+        let second = bc.quote_swap(true, first.vault_out, 1).unwrap();
+        assert_eq!(second.amount_in, first.vault_out);
+        assert_eq!(second.reserve_x, bc.reserve_x + first.amount_out);
+
+        let direct = compute_swap(
+            bc.reserve_x,
+            bc.reserve_y,
+            bc.lp_supply,
+            30,
+            true,
+            first.amount_out,
+            1,
+        )
+        .unwrap();
+        assert_eq!(second.vault_out, direct.withdraw);
+        assert_eq!(second.amount_out, direct.withdraw);
+
+        // 最后一跳的滑点保护作用于整条路由的输出
+        let result = bc.quote_swap(true, first.vault_out, second.amount_out + 1);
+        assert_eq!(result.unwrap_err(), AmmError::SlippageExceeded.into());
+    }
+}
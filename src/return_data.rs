@@ -0,0 +1,287 @@
+use crate::pool::{
+    DepositQuote, DepositSingleQuote, SwapQuote, WithdrawQuote, WithdrawSingleQuote,
+};
+use pinocchio::cpi::set_return_data;
+
+/// ========== 指令返回数据 ==========
+///
+/// Swap、SwapExactOut、RouteSwap、Deposit、DepositByAmounts、DepositSingle、Withdraw 和 WithdrawSingle 执行成功后，通过 `set_return_data` 发布固定布局的结果，
+/// 通过 CPI 调用本程序的合约和交易模拟器无需重新读取代币账户即可获知精确的成交数量。
+/// 只读的 Quote 指令发布相同的布局（交换变体额外追加价格影响）。
+/// 所有字段均为小端序 u64，依字段声明顺序排列。
+///
+/// 储备为操作完成后扣除未提取协议费用的净储备。
+
+/// ========== 交换结果 ==========
+///
+/// 由 Swap、SwapExactOut 和 RouteSwap 发布（RouteSwap 的费用和储备为最后一跳的结果）。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapReturnData {
+    /// 从用户账户转出的输入代币数量（含 Token-2022 转账手续费）
+    pub amount_in: u64,
+    /// 接收账户实际到账的输出代币数量（已扣除 Token-2022 转账手续费）
+    pub amount_out: u64,
+    /// 本次交换支付的交换费用（以输入代币计，含协议分成）
+    pub fee_paid: u64,
+    /// 交换后的代币 X 净储备
+    pub reserve_x: u64,
+    /// 交换后的代币 Y 净储备
+    pub reserve_y: u64,
+}
+
+impl SwapReturnData {
+    /// 序列化后的字节长度
+    pub const LEN: usize = 8 * 5;
+
+    /// 序列化为小端序字节
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0u8; Self::LEN];
+        data[0..8].copy_from_slice(&self.amount_in.to_le_bytes());
+        data[8..16].copy_from_slice(&self.amount_out.to_le_bytes());
+        data[16..24].copy_from_slice(&self.fee_paid.to_le_bytes());
+        data[24..32].copy_from_slice(&self.reserve_x.to_le_bytes());
+        data[32..40].copy_from_slice(&self.reserve_y.to_le_bytes());
+        data
+    }
+
+    /// 从返回数据解析（供客户端和调用方使用），长度不符时返回 `None`
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let data: &[u8; Self::LEN] = data.try_into().ok()?;
+        Some(Self {
+            amount_in: read_u64(data, 0),
+            amount_out: read_u64(data, 1),
+            fee_paid: read_u64(data, 2),
+            reserve_x: read_u64(data, 3),
+            reserve_y: read_u64(data, 4),
+        })
+    }
+
+    /// 发布为当前指令的返回数据
+    #[inline(always)]
+    pub fn set(&self) {
+        set_return_data(&self.to_bytes());
+    }
+}
+
+impl From<&SwapQuote> for SwapReturnData {
+    fn from(quote: &SwapQuote) -> Self {
+        Self {
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee_paid: quote.fee,
+            reserve_x: quote.reserve_x,
+            reserve_y: quote.reserve_y,
+        }
+    }
+}
+
+/// ========== 交换报价结果 ==========
+///
+/// 由 Quote 指令的交换变体发布：在 [`SwapReturnData`] 的布局之后追加价格影响。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuoteReturnData {
+    /// 与 Swap 执行后发布的结果相同
+    pub swap: SwapReturnData,
+    /// 价格影响（bps）：成交价（不含交换费用）相对交换前现货价格的偏离
+    pub price_impact_bps: u64,
+}
+
+impl SwapQuoteReturnData {
+    /// 序列化后的字节长度
+    pub const LEN: usize = SwapReturnData::LEN + 8;
+
+    /// 序列化为小端序字节
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0u8; Self::LEN];
+        data[..SwapReturnData::LEN].copy_from_slice(&self.swap.to_bytes());
+        data[SwapReturnData::LEN..].copy_from_slice(&self.price_impact_bps.to_le_bytes());
+        data
+    }
+
+    /// 从返回数据解析（供客户端和调用方使用），长度不符时返回 `None`
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() != Self::LEN {
+            return None;
+        }
+        Some(Self {
+            swap: SwapReturnData::from_bytes(&data[..SwapReturnData::LEN])?,
+            price_impact_bps: read_u64(data, 5),
+        })
+    }
+
+    /// 发布为当前指令的返回数据
+    #[inline(always)]
+    pub fn set(&self) {
+        set_return_data(&self.to_bytes());
+    }
+}
+
+impl From<&SwapQuote> for SwapQuoteReturnData {
+    fn from(quote: &SwapQuote) -> Self {
+        Self {
+            swap: SwapReturnData::from(quote),
+            price_impact_bps: quote.price_impact_bps,
+        }
+    }
+}
+
+/// ========== 流动性操作结果 ==========
+///
+/// 由 Deposit、DepositByAmounts、DepositSingle、Withdraw 和 WithdrawSingle 发布。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LiquidityReturnData {
+    /// 代币 X 数量：Deposit 为从用户账户转出的数量，Withdraw 为用户实际到账的数量
+    pub amount_x: u64,
+    /// 代币 Y 数量：Deposit 为从用户账户转出的数量，Withdraw 为用户实际到账的数量
+    pub amount_y: u64,
+    /// LP 数量：Deposit 为铸造给用户的数量，Withdraw 为销毁的数量
+    pub amount_lp: u64,
+    /// 隐含交换支付的交换费用（含协议分成）：DepositSingle 以输入代币计，WithdrawSingle 以另一侧代币计，
+    /// 按比例的 Deposit、DepositByAmounts 和 Withdraw 不发生交换，为 0
+    pub fee_paid: u64,
+    /// 操作后的代币 X 净储备
+    pub reserve_x: u64,
+    /// 操作后的代币 Y 净储备
+    pub reserve_y: u64,
+}
+
+impl LiquidityReturnData {
+    /// 序列化后的字节长度
+    pub const LEN: usize = 8 * 6;
+
+    /// 序列化为小端序字节
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        let mut data = [0u8; Self::LEN];
+        data[0..8].copy_from_slice(&self.amount_x.to_le_bytes());
+        data[8..16].copy_from_slice(&self.amount_y.to_le_bytes());
+        data[16..24].copy_from_slice(&self.amount_lp.to_le_bytes());
+        data[24..32].copy_from_slice(&self.fee_paid.to_le_bytes());
+        data[32..40].copy_from_slice(&self.reserve_x.to_le_bytes());
+        data[40..48].copy_from_slice(&self.reserve_y.to_le_bytes());
+        data
+    }
+
+    /// 从返回数据解析（供客户端和调用方使用），长度不符时返回 `None`
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        let data: &[u8; Self::LEN] = data.try_into().ok()?;
+        Some(Self {
+            amount_x: read_u64(data, 0),
+            amount_y: read_u64(data, 1),
+            amount_lp: read_u64(data, 2),
+            fee_paid: read_u64(data, 3),
+            reserve_x: read_u64(data, 4),
+            reserve_y: read_u64(data, 5),
+        })
+    }
+
+    /// 发布为当前指令的返回数据
+    #[inline(always)]
+    pub fn set(&self) {
+        set_return_data(&self.to_bytes());
+    }
+}
+
+impl From<&DepositQuote> for LiquidityReturnData {
+    fn from(quote: &DepositQuote) -> Self {
+        Self {
+            amount_x: quote.amount_x,
+            amount_y: quote.amount_y,
+            amount_lp: quote.amount_lp,
+            fee_paid: 0,
+            reserve_x: quote.reserve_x,
+            reserve_y: quote.reserve_y,
+        }
+    }
+}
+
+impl From<&WithdrawQuote> for LiquidityReturnData {
+    fn from(quote: &WithdrawQuote) -> Self {
+        Self {
+            amount_x: quote.amount_x,
+            amount_y: quote.amount_y,
+            amount_lp: quote.amount_lp,
+            fee_paid: 0,
+            reserve_x: quote.reserve_x,
+            reserve_y: quote.reserve_y,
+        }
+    }
+}
+
+impl From<&DepositSingleQuote> for LiquidityReturnData {
+    fn from(quote: &DepositSingleQuote) -> Self {
+        Self {
+            fee_paid: quote.fee,
+            ..Self::from(&quote.deposit)
+        }
+    }
+}
+
+impl From<&WithdrawSingleQuote> for LiquidityReturnData {
+    fn from(quote: &WithdrawSingleQuote) -> Self {
+        Self {
+            fee_paid: quote.fee,
+            ..Self::from(&quote.withdraw)
+        }
+    }
+}
+
+/// 读取第 `index` 个小端序 u64 字段
+#[inline(always)]
+fn read_u64(data: &[u8], index: usize) -> u64 {
+    let offset = index * 8;
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swap_return_data_round_trip() {
+        let data = SwapReturnData {
+            amount_in: 1,
+            amount_out: 2,
+            fee_paid: 3,
+            reserve_x: u64::MAX,
+            reserve_y: 5,
+        };
+        assert_eq!(SwapReturnData::from_bytes(&data.to_bytes()), Some(data));
+        assert_eq!(SwapReturnData::from_bytes(&[0u8; 39]), None);
+    }
+
+    #[test]
+    fn swap_quote_return_data_extends_swap_layout() {
+        let data = SwapQuoteReturnData {
+            swap: SwapReturnData {
+                amount_in: 7,
+                amount_out: 6,
+                fee_paid: 1,
+                reserve_x: 100,
+                reserve_y: 200,
+            },
+            price_impact_bps: 42,
+        };
+        let bytes = data.to_bytes();
+        assert_eq!(
+            SwapReturnData::from_bytes(&bytes[..SwapReturnData::LEN]),
+            Some(data.swap)
+        );
+        assert_eq!(SwapQuoteReturnData::from_bytes(&bytes), Some(data));
+    }
+
+    #[test]
+    fn liquidity_return_data_round_trip() {
+        let data = LiquidityReturnData {
+            amount_x: 10,
+            amount_y: 20,
+            amount_lp: 30,
+            fee_paid: 5,
+            reserve_x: 40,
+            reserve_y: u64::MAX,
+        };
+        let bytes = data.to_bytes();
+        assert_eq!(&bytes[16..24], &30u64.to_le_bytes());
+        assert_eq!(&bytes[24..32], &5u64.to_le_bytes());
+        assert_eq!(LiquidityReturnData::from_bytes(&bytes), Some(data));
+    }
+}
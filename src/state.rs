@@ -1,10 +1,19 @@
 use core::mem::size_of;
+use crate::error::AmmError;
 use pinocchio::{
     AccountView, Address,
     account::{Ref, RefMut},
+    cpi::Seed,
     error::ProgramError,
 };
 
+/// ========== 最小流动性 ==========
+///
+/// 首次向空池存入时永久锁定的 LP 代币数量（参考 Uniswap V2）。
+/// 这部分 LP 被铸造到 Config PDA 持有的 LP 代币账户中，没有任何指令可以转出或销毁，
+/// 从而使首个存入者通过捐赠抬高份额价格的攻击变得不经济。
+pub const MINIMUM_LIQUIDITY: u64 = 1_000;
+
 /// ========== AMM 配置账户结构 ==========
 /// 
 /// Config 是 AMM 流动性池的核心配置账户，存储了整个流动性池的元数据信息。
@@ -17,20 +26,51 @@ pub struct Config {
     seed: [u8; 8],
     /// AMM 的权限管理员地址（可以升级或暂停 AMM）
     authority: Address,
+    /// 待接受的新权限管理员地址（两步转移权限，全 0 表示没有待处理的转移）
+    pending_authority: Address,
     /// 代币 X 的 mint 地址（流动性池中的第一种代币）
     mint_x: Address,
     /// 代币 Y 的 mint 地址（流动性池中的第二种代币）
     mint_y: Address,
+    /// 代币 X 所属的 Token 程序（SPL Token 或 Token-2022）
+    token_program_x: Address,
+    /// 代币 Y 所属的 Token 程序（SPL Token 或 Token-2022）
+    token_program_y: Address,
     /// 交换费用（以 bps 计，即万分之一，例如 0-9999 表示 0%-99.99%）
     fee: [u8; 2],
+    /// 协议分成比例（以 bps 计，表示交换费用中归协议金库的比例，0-10000）
+    protocol_fee_share: [u8; 2],
+    /// 已累计、尚未提取的代币 X 协议费用
+    protocol_fees_owed_x: [u8; 8],
+    /// 已累计、尚未提取的代币 Y 协议费用
+    protocol_fees_owed_y: [u8; 8],
     /// 生成配置账户 PDA 时的 bump seed 值
     config_bump: [u8; 1],
+    /// 生成 LP mint PDA 时的 canonical bump seed 值
+    lp_bump: [u8; 1],
+    /// LP mint 的精度（仅影响 LP 数量的显示，不参与存入 / 提取的比例计算）
+    lp_decimals: u8,
+    /// 闪电贷费用（以 bps 计，按借出数量收取，0-9999）
+    flash_fee: [u8; 2],
+    /// 重入保护标志：闪电贷进行中为 1，此时交换、存入、提取均被拒绝
+    flash_loan_active: u8,
 }
 
 /// ========== AMM 状态枚举 ==========
 /// 
 /// 定义了 AMM 可能处于的各种状态，用来控制池的可用性和操作限制。
+///
+/// 状态转换规则：
+/// * `Uninitialized` 只能转换为 `Initialized`（由 Initialize 指令完成）
+/// * `Initialized`、`Disabled`、`WithdrawOnly` 之间可以任意切换（含保持不变）
+/// * 任何状态都不能回到 `Uninitialized`
+///
+/// 各指令的允许状态：
+/// * Swap / RouteSwap / Deposit：仅 `Initialized`
+/// * Withdraw：`Initialized` 或 `WithdrawOnly`
+/// * FlashLoan / FlashSwap：仅 `Initialized`
 #[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AmmState {
     /// 状态 0：未初始化，池账户已创建但数据未填充
     Uninitialized = 0u8,
@@ -42,10 +82,96 @@ pub enum AmmState {
     WithdrawOnly = 3u8,
 }
 
+impl TryFrom<u8> for AmmState {
+    type Error = ProgramError;
+
+    /// 将原始状态字节转换为 AmmState，未知取值返回 InvalidState
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AmmState::Uninitialized),
+            1 => Ok(AmmState::Initialized),
+            2 => Ok(AmmState::Disabled),
+            3 => Ok(AmmState::WithdrawOnly),
+            _ => Err(AmmError::InvalidState.into()),
+        }
+    }
+}
+
+impl AmmState {
+    /// 判断是否允许从当前状态转换到 `next`
+    #[inline(always)]
+    pub fn can_transition_to(self, next: AmmState) -> bool {
+        use AmmState::*;
+        match (self, next) {
+            (Uninitialized, Initialized) => true,
+            (Initialized | Disabled | WithdrawOnly, Initialized | Disabled | WithdrawOnly) => true,
+            (_, Uninitialized) | (Uninitialized, Disabled | WithdrawOnly) => false,
+        }
+    }
+
+    /// 当前状态是否允许交换
+    #[inline(always)]
+    pub fn allows_swap(self) -> bool {
+        self == AmmState::Initialized
+    }
+
+    /// 当前状态是否允许存入流动性
+    #[inline(always)]
+    pub fn allows_deposit(self) -> bool {
+        self == AmmState::Initialized
+    }
+
+    /// 当前状态是否允许提取流动性
+    #[inline(always)]
+    pub fn allows_withdraw(self) -> bool {
+        matches!(self, AmmState::Initialized | AmmState::WithdrawOnly)
+    }
+}
+
 impl Config {
     /// 配置账户的固定大小（以字节为单位）
     pub const LEN: usize = size_of::<Config>();
 
+    /// ========== 规范化代币对顺序 ==========
+    ///
+    /// 按字节序排列两个 mint，返回 `(mint_x, mint_y)`，保证 `mint_x < mint_y`。
+    /// 每个代币对在同一 seed 下只存在一个池，客户端推导地址前必须先排序。
+    #[inline(always)]
+    pub fn sort_mints(mint_a: Address, mint_b: Address) -> (Address, Address) {
+        let (a, b): (&[u8], &[u8]) = (mint_a.as_ref(), mint_b.as_ref());
+        if a <= b {
+            (mint_a, mint_b)
+        } else {
+            (mint_b, mint_a)
+        }
+    }
+
+    /// ========== 推导 Config PDA ==========
+    ///
+    /// 先按 [`Config::sort_mints`] 规范化代币对顺序，再推导 canonical 的
+    /// Config 地址和 bump。链上 Initialize 与客户端共用同一推导逻辑。
+    #[inline(always)]
+    pub fn find_address(seed: u64, mint_a: Address, mint_b: Address) -> (Address, u8) {
+        let (mint_x, mint_y) = Self::sort_mints(mint_a, mint_b);
+        Address::find_program_address(
+            &[
+                b"config",
+                &seed.to_le_bytes(),
+                mint_x.as_ref(),
+                mint_y.as_ref(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    /// ========== 推导 LP mint PDA ==========
+    ///
+    /// 根据 Config 地址推导 canonical 的 LP mint 地址和 bump。
+    #[inline(always)]
+    pub fn find_mint_lp_address(config: &Address) -> (Address, u8) {
+        Address::find_program_address(&[b"mint_lp", config.as_ref()], &crate::ID)
+    }
+
     /// ========== 加载 Config 账户数据（只读） ==========
     /// 
     /// 从 Solana 账户中安全地加载 Config 结构体的只读副本。
@@ -141,6 +267,12 @@ impl Config {
         self.state
     }
 
+    /// 获取 AMM 当前的状态（解析为 AmmState）
+    #[inline(always)]
+    pub fn amm_state(&self) -> Result<AmmState, ProgramError> {
+        AmmState::try_from(self.state)
+    }
+
     /// 获取池的种子值（8 字节，转换为 u64）
     #[inline(always)]
     pub fn seed(&self) -> u64 {
@@ -153,6 +285,12 @@ impl Config {
         &self.authority
     }
 
+    /// 获取待接受的新权限管理员地址的引用
+    #[inline(always)]
+    pub fn pending_authority(&self) -> &Address {
+        &self.pending_authority
+    }
+
     /// 获取代币 X mint 地址的引用
     #[inline(always)]
     pub fn mint_x(&self) -> &Address {
@@ -165,18 +303,116 @@ impl Config {
         &self.mint_y
     }
 
+    /// 获取代币 X 所属 Token 程序地址的引用
+    #[inline(always)]
+    pub fn token_program_x(&self) -> &Address {
+        &self.token_program_x
+    }
+
+    /// 获取代币 Y 所属 Token 程序地址的引用
+    #[inline(always)]
+    pub fn token_program_y(&self) -> &Address {
+        &self.token_program_y
+    }
+
     /// 获取交换费用（以 bps 计，范围 0-9999）
     #[inline(always)]
     pub fn fee(&self) -> u16 {
         u16::from_le_bytes(self.fee)
     }
 
+    /// 获取协议分成比例（以 bps 计，范围 0-10000）
+    #[inline(always)]
+    pub fn protocol_fee_share(&self) -> u16 {
+        u16::from_le_bytes(self.protocol_fee_share)
+    }
+
+    /// 获取已累计的代币 X 协议费用
+    #[inline(always)]
+    pub fn protocol_fees_owed_x(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_owed_x)
+    }
+
+    /// 获取已累计的代币 Y 协议费用
+    #[inline(always)]
+    pub fn protocol_fees_owed_y(&self) -> u64 {
+        u64::from_le_bytes(self.protocol_fees_owed_y)
+    }
+
     /// 获取配置账户的 bump seed
     #[inline(always)]
     pub fn config_bump(&self) -> [u8; 1] {
         self.config_bump
     }
 
+    /// 获取 Config PDA 的签名种子 `[b"config", seed, mint_x, mint_y, bump]`
+    ///
+    /// 种子直接引用 Config 中存储的字节，金库转出、铸造 LP 等需要 Config 签名的 CPI 共用。
+    #[inline(always)]
+    pub fn signer_seeds(&self) -> [Seed<'_>; 5] {
+        [
+            Seed::from(b"config"),
+            Seed::from(&self.seed),
+            Seed::from(self.mint_x.as_ref()),
+            Seed::from(self.mint_y.as_ref()),
+            Seed::from(&self.config_bump),
+        ]
+    }
+
+    /// 获取 LP mint PDA 的 bump seed
+    #[inline(always)]
+    pub fn lp_bump(&self) -> [u8; 1] {
+        self.lp_bump
+    }
+
+    /// 获取 LP mint 的精度
+    #[inline(always)]
+    pub fn lp_decimals(&self) -> u8 {
+        self.lp_decimals
+    }
+
+    /// 获取闪电贷费用（以 bps 计，范围 0-9999）
+    #[inline(always)]
+    pub fn flash_fee(&self) -> u16 {
+        u16::from_le_bytes(self.flash_fee)
+    }
+
+    /// 是否有闪电贷正在进行
+    #[inline(always)]
+    pub fn flash_loan_active(&self) -> bool {
+        self.flash_loan_active != 0
+    }
+
+    /// 验证当前没有进行中的闪电贷
+    ///
+    /// 闪电贷期间金库余额暂时减少，任何依赖储备的计算都不可信，返回 FlashLoanActive。
+    #[inline(always)]
+    pub fn check_no_flash_loan(&self) -> Result<(), ProgramError> {
+        if self.flash_loan_active() {
+            return Err(AmmError::FlashLoanActive.into());
+        }
+        Ok(())
+    }
+
+    /// 计算曲线使用的净储备
+    ///
+    /// 金库余额中包含尚未提取的协议费用，这部分不属于 LP，
+    /// 因此交换、存入和提取的计算都必须使用扣除协议费用后的储备。
+    #[inline(always)]
+    pub fn reserves(
+        &self,
+        vault_x_amount: u64,
+        vault_y_amount: u64,
+    ) -> Result<(u64, u64), ProgramError> {
+        let x = vault_x_amount
+            .checked_sub(self.protocol_fees_owed_x())
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        let y = vault_y_amount
+            .checked_sub(self.protocol_fees_owed_y())
+            .ok_or(ProgramError::from(AmmError::CurveError))?;
+        Ok((x, y))
+    }
+
     /// ========== 加载 Config 账户数据（可变） ==========
     /// 
     /// 安全地加载 Config 结构体的可变引用，用于修改池的配置。
@@ -205,12 +441,12 @@ impl Config {
     // ========== 设置器方法（Setter 方法） ==========
     // 这些方法提供对各个字段的安全写入访问，包含验证逻辑
 
-    /// 设置 AMM 的状态，并验证状态值的有效性
+    /// 设置 AMM 的状态，验证状态值的有效性以及从当前状态转换的合法性
     #[inline(always)]
     pub fn set_state(&mut self, state: u8) -> Result<(), ProgramError> {
-        // 确保状态不超过 WithdrawOnly
-        if state.ge(&(AmmState::WithdrawOnly as u8)) {
-            return Err(ProgramError::InvalidAccountData);
+        let next = AmmState::try_from(state)?;
+        if !self.amm_state()?.can_transition_to(next) {
+            return Err(AmmError::InvalidState.into());
         }
         self.state = state;
         Ok(())
@@ -228,6 +464,12 @@ impl Config {
         self.authority = authority;
     }
 
+    /// 设置待接受的新权限管理员地址（全 0 表示取消待处理的转移）
+    #[inline(always)]
+    pub fn set_pending_authority(&mut self, pending_authority: Address) {
+        self.pending_authority = pending_authority;
+    }
+
     /// 设置代币 X 的 mint 地址
     #[inline(always)]
     pub fn set_mint_x(&mut self, mint_x: Address) {
@@ -240,22 +482,104 @@ impl Config {
         self.mint_y = mint_y;
     }
 
+    /// 设置代币 X 所属的 Token 程序
+    #[inline(always)]
+    pub fn set_token_program_x(&mut self, token_program_x: Address) {
+        self.token_program_x = token_program_x;
+    }
+
+    /// 设置代币 Y 所属的 Token 程序
+    #[inline(always)]
+    pub fn set_token_program_y(&mut self, token_program_y: Address) {
+        self.token_program_y = token_program_y;
+    }
+
     /// 设置交换费用，并验证费用不超过 10000 bps（100%）
     #[inline(always)]
     pub fn set_fee(&mut self, fee: u16) -> Result<(), ProgramError> {
         if fee.ge(&10_000) {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(AmmError::InvalidFee.into());
         }
         self.fee = fee.to_le_bytes();
         Ok(())
     }
 
+    /// 设置协议分成比例，并验证不超过 10000 bps（100%）
+    #[inline(always)]
+    pub fn set_protocol_fee_share(&mut self, protocol_fee_share: u16) -> Result<(), ProgramError> {
+        if protocol_fee_share.gt(&10_000) {
+            return Err(AmmError::InvalidFee.into());
+        }
+        self.protocol_fee_share = protocol_fee_share.to_le_bytes();
+        Ok(())
+    }
+
+    /// 设置闪电贷费用，并验证费用不超过 10000 bps（100%）
+    #[inline(always)]
+    pub fn set_flash_fee(&mut self, flash_fee: u16) -> Result<(), ProgramError> {
+        if flash_fee.ge(&10_000) {
+            return Err(AmmError::InvalidFee.into());
+        }
+        self.flash_fee = flash_fee.to_le_bytes();
+        Ok(())
+    }
+
+    /// 设置闪电贷重入保护标志
+    #[inline(always)]
+    pub fn set_flash_loan_active(&mut self, active: bool) {
+        self.flash_loan_active = active as u8;
+    }
+
+    /// 设置已累计的代币 X 协议费用
+    #[inline(always)]
+    pub fn set_protocol_fees_owed_x(&mut self, amount: u64) {
+        self.protocol_fees_owed_x = amount.to_le_bytes();
+    }
+
+    /// 设置已累计的代币 Y 协议费用
+    #[inline(always)]
+    pub fn set_protocol_fees_owed_y(&mut self, amount: u64) {
+        self.protocol_fees_owed_y = amount.to_le_bytes();
+    }
+
+    /// 累计一笔交换产生的协议费用（`is_x` 表示费用以代币 X 计）
+    #[inline(always)]
+    pub fn accrue_protocol_fee(&mut self, is_x: bool, amount: u64) -> Result<(), ProgramError> {
+        if is_x {
+            let owed = self
+                .protocol_fees_owed_x()
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.set_protocol_fees_owed_x(owed);
+        } else {
+            let owed = self
+                .protocol_fees_owed_y()
+                .checked_add(amount)
+                .ok_or(ProgramError::ArithmeticOverflow)?;
+            self.set_protocol_fees_owed_y(owed);
+        }
+        Ok(())
+    }
+
     /// 设置配置账户的 bump seed
     #[inline(always)]
     pub fn set_config_bump(&mut self, config_bump: [u8; 1]) {
         self.config_bump = config_bump;
     }
 
+    /// 设置 LP mint PDA 的 bump seed
+    #[inline(always)]
+    pub fn set_lp_bump(&mut self, lp_bump: [u8; 1]) {
+        self.lp_bump = lp_bump;
+    }
+
+    /// 设置 LP mint 的精度
+    #[inline(always)]
+    pub fn set_lp_decimals(&mut self, lp_decimals: u8) {
+        self.lp_decimals = lp_decimals;
+    }
+
+    #[allow(clippy::too_many_arguments)]
     #[inline(always)]
     pub fn set_inner(
         &mut self,
@@ -263,16 +587,30 @@ impl Config {
         authority: Address,
         mint_x: Address,
         mint_y: Address,
+        token_program_x: Address,
+        token_program_y: Address,
         fee: u16,
         config_bump: [u8; 1],
+        lp_bump: [u8; 1],
+        lp_decimals: u8,
     ) -> Result<(), ProgramError> {
         self.set_state(AmmState::Initialized as u8)?;
         self.set_seed(seed);
         self.set_authority(authority);
+        self.set_pending_authority(Address::default());
         self.set_mint_x(mint_x);
         self.set_mint_y(mint_y);
+        self.set_token_program_x(token_program_x);
+        self.set_token_program_y(token_program_y);
         self.set_fee(fee)?;
+        self.set_protocol_fee_share(0)?;
+        self.set_protocol_fees_owed_x(0);
+        self.set_protocol_fees_owed_y(0);
         self.set_config_bump(config_bump);
+        self.set_lp_bump(lp_bump);
+        self.set_lp_decimals(lp_decimals);
+        self.set_flash_fee(0)?;
+        self.set_flash_loan_active(false);
         Ok(())
     }
 
@@ -294,6 +632,31 @@ impl Config {
         }
     }
 
+    /// 获取待接受的新权限管理员，没有待处理的转移时返回 None
+    #[inline(always)]
+    pub fn has_pending_authority(&self) -> Option<Address> {
+        let pending = unsafe { core::ptr::addr_of!(self.pending_authority).read_unaligned() };
+
+        if pending == Address::default() {
+            None
+        } else {
+            Some(pending)
+        }
+    }
+
+    /// 验证 `signer` 是池的权限管理员
+    ///
+    /// 没有权限管理员的池配置被永久锁定，返回 ConfigLocked；
+    /// 签名者与权限管理员不一致时返回 Unauthorized。
+    #[inline(always)]
+    pub fn check_authority(&self, signer: &Address) -> Result<(), ProgramError> {
+        match self.has_authority() {
+            None => Err(AmmError::ConfigLocked.into()),
+            Some(authority) if authority != *signer => Err(AmmError::Unauthorized.into()),
+            Some(_) => Ok(()),
+        }
+    }
+
     /// 强制以可变引用加载账户数据，不检查所有者 (用于初始化)
     /// # Safety
     /// 调用者必须确保账户空间足够且已由程序控制
@@ -308,4 +671,119 @@ impl Config {
         // 直接获取账户数据的原始指针并转换为可变结构体引用
         Ok(unsafe { Self::from_bytes_unchecked_mut(account_view.borrow_unchecked_mut()) })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_STATES: [AmmState; 4] = [
+        AmmState::Uninitialized,
+        AmmState::Initialized,
+        AmmState::Disabled,
+        AmmState::WithdrawOnly,
+    ];
+
+    /// 期望的状态转换表：ALLOWED[from][to]
+    const ALLOWED: [[bool; 4]; 4] = [
+        // Uninitialized -> U, I, D, W
+        [false, true, false, false],
+        // Initialized -> U, I, D, W
+        [false, true, true, true],
+        // Disabled -> U, I, D, W
+        [false, true, true, true],
+        // WithdrawOnly -> U, I, D, W
+        [false, true, true, true],
+    ];
+
+    #[test]
+    fn state_try_from_u8() {
+        for state in ALL_STATES {
+            assert_eq!(AmmState::try_from(state as u8).unwrap(), state);
+        }
+        assert_eq!(
+            AmmState::try_from(4).unwrap_err(),
+            ProgramError::from(AmmError::InvalidState)
+        );
+    }
+
+    #[test]
+    fn can_transition_to_matches_table() {
+        for from in ALL_STATES {
+            for to in ALL_STATES {
+                assert_eq!(
+                    from.can_transition_to(to),
+                    ALLOWED[from as usize][to as usize],
+                    "{:?} -> {:?}",
+                    from,
+                    to
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn set_state_enforces_transitions() {
+        for from in ALL_STATES {
+            for to in ALL_STATES {
+                let mut bytes = [0u8; Config::LEN];
+                let config = unsafe { Config::from_bytes_unchecked_mut(&mut bytes) };
+                config.state = from as u8;
+
+                let result = config.set_state(to as u8);
+                if ALLOWED[from as usize][to as usize] {
+                    assert!(result.is_ok(), "{:?} -> {:?}", from, to);
+                    assert_eq!(config.state(), to as u8);
+                } else {
+                    assert_eq!(result.unwrap_err(), ProgramError::from(AmmError::InvalidState));
+                    assert_eq!(config.state(), from as u8);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn set_state_rejects_unknown_value() {
+        let mut bytes = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut bytes) };
+        config.state = AmmState::Initialized as u8;
+        assert!(config.set_state(4).is_err());
+        assert_eq!(config.state(), AmmState::Initialized as u8);
+    }
+
+    #[test]
+    fn flash_fee_and_guard() {
+        let mut bytes = [0u8; Config::LEN];
+        let config = unsafe { Config::from_bytes_unchecked_mut(&mut bytes) };
+
+        assert!(config.set_flash_fee(9).is_ok());
+        assert_eq!(config.flash_fee(), 9);
+        assert_eq!(
+            config.set_flash_fee(10_000).unwrap_err(),
+            ProgramError::from(AmmError::InvalidFee)
+        );
+        assert_eq!(config.flash_fee(), 9);
+
+        assert!(config.check_no_flash_loan().is_ok());
+        config.set_flash_loan_active(true);
+        assert_eq!(
+            config.check_no_flash_loan().unwrap_err(),
+            ProgramError::from(AmmError::FlashLoanActive)
+        );
+        config.set_flash_loan_active(false);
+        assert!(config.check_no_flash_loan().is_ok());
+    }
+
+    #[test]
+    fn instruction_permissions() {
+        for state in ALL_STATES {
+            let initialized = state == AmmState::Initialized;
+            assert_eq!(state.allows_swap(), initialized);
+            assert_eq!(state.allows_deposit(), initialized);
+            assert_eq!(
+                state.allows_withdraw(),
+                initialized || state == AmmState::WithdrawOnly
+            );
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use crate::error::AmmError;
+
+use pinocchio::{
+    cpi::{invoke_signed, Signer},
+    error::ProgramError,
+    instruction::{InstructionAccount, InstructionView},
+    sysvars::{clock::Clock, Sysvar},
+    AccountView, Address, ProgramResult,
+};
+use pinocchio_token::state::{Mint, TokenAccount};
+use pinocchio_token::ID as TOKEN_PROGRAM_ID;
+
+/// ========== Token 程序兼容层 ==========
+///
+/// 池的代币 X / Y 可以属于 SPL Token 或 Token-2022 程序。
+/// 本模块统一处理两种程序的账户格式校验、mint 扩展检查以及 `TransferChecked` 转账，
+/// 各指令通过 Config 中记录的 token program 调用这些辅助函数。
+
+/// Token-2022 程序 ID（TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb）
+pub const TOKEN_2022_PROGRAM_ID: Address = Address::new_from_array([
+    6, 221, 246, 225, 238, 117, 143, 222, 24, 66, 93, 188, 228, 108, 205, 218, 182, 26, 252, 77,
+    131, 185, 13, 39, 254, 189, 249, 40, 216, 161, 139, 252,
+]);
+
+/// Token-2022 扩展账户中账户类型字节的偏移（紧随 165 字节的基础账户布局）
+const ACCOUNT_TYPE_OFFSET: usize = TokenAccount::LEN;
+/// 账户类型：Mint
+const ACCOUNT_TYPE_MINT: u8 = 1;
+/// 账户类型：代币账户
+const ACCOUNT_TYPE_ACCOUNT: u8 = 2;
+
+/// 扩展类型：转账手续费配置（TransferFeeConfig）
+const EXTENSION_TRANSFER_FEE_CONFIG: u16 = 1;
+/// TransferFeeConfig 中较旧一档费率（older_transfer_fee）的偏移
+const OLDER_TRANSFER_FEE_OFFSET: usize = 72;
+/// TransferFeeConfig 中较新一档费率（newer_transfer_fee）的偏移
+const NEWER_TRANSFER_FEE_OFFSET: usize = 90;
+/// 手续费基点上限（100%）
+const MAX_FEE_BASIS_POINTS: u64 = 10_000;
+
+/// ========== 允许的 Token-2022 mint 扩展 ==========
+///
+/// 只有不能冻结或挪用金库资产的扩展才被允许；转账手续费（TransferFeeConfig）
+/// 会在各指令中按当前费率折算实际到账数量。
+/// 永久委托（PermanentDelegate）、不可转让（NonTransferable）、转账钩子（TransferHook）、
+/// 默认冻结（DefaultAccountState）、可暂停（Pausable）、机密转账以及所有未知扩展都会在
+/// Initialize 时被拒绝。
+/// 关闭权限（MintCloseAuthority）同样被拒绝：供应量为 0 时 mint 可以被关闭，
+/// 并在同一地址以不同的扩展重新创建，从而绕过 Initialize 时的扩展检查。
+const ALLOWED_MINT_EXTENSIONS: [u16; 9] = [
+    EXTENSION_TRANSFER_FEE_CONFIG,
+    10, // InterestBearingConfig（仅影响 UI 显示数量）
+    18, // MetadataPointer
+    19, // TokenMetadata
+    20, // GroupPointer
+    21, // TokenGroup
+    22, // GroupMemberPointer
+    23, // TokenGroupMember
+    25, // ScaledUiAmount（仅影响 UI 显示数量）
+];
+
+/// 判断地址是否为本池支持的 Token 程序
+#[inline(always)]
+pub fn is_token_program(address: &Address) -> bool {
+    address == &TOKEN_PROGRAM_ID || address == &TOKEN_2022_PROGRAM_ID
+}
+
+/// ========== 验证 mint 账户 ==========
+///
+/// 验证账户由 `token_program` 拥有且是 mint 账户。
+/// Token-2022 mint 可以带扩展，此时数据长度大于 `Mint::LEN`，并以账户类型字节标识。
+pub fn check_mint_account(mint: &AccountView, token_program: &Address) -> ProgramResult {
+    if !mint.owned_by(token_program) {
+        return Err(AmmError::InvalidMint.into());
+    }
+    let len = mint.data_len();
+    if len == Mint::LEN {
+        return Ok(());
+    }
+    if token_program == &TOKEN_2022_PROGRAM_ID && len > ACCOUNT_TYPE_OFFSET {
+        let data = mint.try_borrow()?;
+        if data[ACCOUNT_TYPE_OFFSET] == ACCOUNT_TYPE_MINT {
+            return Ok(());
+        }
+    }
+    Err(AmmError::InvalidMint.into())
+}
+
+/// ========== 验证代币账户 ==========
+///
+/// 验证账户由 `token_program` 拥有且是代币账户。
+/// Token-2022 的关联代币账户默认带 ImmutableOwner 扩展，数据长度大于 `TokenAccount::LEN`。
+pub fn check_token_account(account: &AccountView, token_program: &Address) -> ProgramResult {
+    if !account.owned_by(token_program) {
+        return Err(ProgramError::InvalidAccountOwner);
+    }
+    let len = account.data_len();
+    if len == TokenAccount::LEN {
+        return Ok(());
+    }
+    if token_program == &TOKEN_2022_PROGRAM_ID && len > ACCOUNT_TYPE_OFFSET {
+        let data = account.try_borrow()?;
+        if data[ACCOUNT_TYPE_OFFSET] == ACCOUNT_TYPE_ACCOUNT {
+            return Ok(());
+        }
+    }
+    Err(ProgramError::InvalidAccountOwner)
+}
+
+/// ========== 读取 mint 基础数据（不检查） ==========
+///
+/// # Safety
+/// 调用者必须已通过 [`check_mint_account`] 验证账户，且不存在并发的可变借用。
+#[inline(always)]
+pub unsafe fn mint_unchecked(mint: &AccountView) -> &Mint {
+    unsafe { Mint::from_bytes_unchecked(&mint.borrow_unchecked()[..Mint::LEN]) }
+}
+
+/// ========== 读取代币账户基础数据（不检查） ==========
+///
+/// # Safety
+/// 调用者必须已通过 [`check_token_account`] 验证账户，且不存在并发的可变借用。
+#[inline(always)]
+pub unsafe fn token_account_unchecked(account: &AccountView) -> &TokenAccount {
+    unsafe { TokenAccount::from_bytes_unchecked(&account.borrow_unchecked()[..TokenAccount::LEN]) }
+}
+
+/// ========== 检查 mint 扩展 ==========
+///
+/// 遍历 Token-2022 mint 的 TLV 扩展列表，遇到不在允许列表中的扩展即拒绝。
+/// SPL Token mint 没有扩展，直接通过。
+pub fn check_mint_extensions(mint: &AccountView) -> ProgramResult {
+    let data = mint.try_borrow()?;
+
+    for (extension_type, _) in extensions(&data) {
+        if !ALLOWED_MINT_EXTENSIONS.contains(&extension_type) {
+            return Err(AmmError::UnsupportedMintExtension.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// ========== 遍历 Token-2022 扩展 ==========
+///
+/// 返回 `(扩展类型, 扩展数据)` 迭代器。SPL Token 账户没有扩展，迭代器为空。
+fn extensions(data: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    // TLV 数据从账户类型字节之后开始：[类型 u16][长度 u16][数据]
+    let mut offset = if data.len() > ACCOUNT_TYPE_OFFSET {
+        ACCOUNT_TYPE_OFFSET + 1
+    } else {
+        data.len()
+    };
+
+    core::iter::from_fn(move || {
+        if offset + 4 > data.len() {
+            return None;
+        }
+        let extension_type = u16::from_le_bytes([data[offset], data[offset + 1]]);
+        let extension_len = u16::from_le_bytes([data[offset + 2], data[offset + 3]]) as usize;
+
+        // 类型 0 表示未初始化，其后均为填充
+        if extension_type == 0 {
+            return None;
+        }
+
+        let start = offset + 4;
+        let end = start.checked_add(extension_len)?;
+        if end > data.len() {
+            return None;
+        }
+        offset = end;
+        Some((extension_type, &data[start..end]))
+    })
+}
+
+/// ========== 转账手续费 ==========
+///
+/// Token-2022 TransferFee 扩展在某一 epoch 生效的费率。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransferFee {
+    /// 单笔转账手续费上限
+    pub maximum_fee: u64,
+    /// 手续费基点（万分之一）
+    pub basis_points: u16,
+}
+
+impl TransferFee {
+    /// 不收取手续费（SPL Token mint 或未启用 TransferFee 扩展）
+    pub const ZERO: Self = Self {
+        maximum_fee: 0,
+        basis_points: 0,
+    };
+
+    /// 转出 `amount` 时被扣留的手续费（向上取整，且不超过上限），与 Token-2022 的计算一致
+    pub fn fee(&self, amount: u64) -> Result<u64, ProgramError> {
+        if self.basis_points == 0 || amount == 0 {
+            return Ok(0);
+        }
+        let numerator = (amount as u128)
+            .checked_mul(self.basis_points as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let fee = numerator.div_ceil(MAX_FEE_BASIS_POINTS as u128);
+        Ok(core::cmp::min(fee, self.maximum_fee as u128) as u64)
+    }
+
+    /// 转出 `amount` 后接收方实际到账的数量
+    pub fn post_fee_amount(&self, amount: u64) -> Result<u64, ProgramError> {
+        Ok(amount - self.fee(amount)?)
+    }
+
+    /// 为使接收方实际到账 `post_fee_amount`，需要转出的最少数量
+    pub fn pre_fee_amount(&self, post_fee_amount: u64) -> Result<u64, ProgramError> {
+        if self.basis_points == 0 || self.maximum_fee == 0 || post_fee_amount == 0 {
+            return Ok(post_fee_amount);
+        }
+        if self.basis_points as u64 >= MAX_FEE_BASIS_POINTS {
+            return post_fee_amount
+                .checked_add(self.maximum_fee)
+                .ok_or(ProgramError::ArithmeticOverflow);
+        }
+
+        let numerator = (post_fee_amount as u128) * MAX_FEE_BASIS_POINTS as u128;
+        let denominator = MAX_FEE_BASIS_POINTS as u128 - self.basis_points as u128;
+        let raw_pre_fee_amount = numerator.div_ceil(denominator);
+
+        let pre_fee_amount = if raw_pre_fee_amount - post_fee_amount as u128
+            >= self.maximum_fee as u128
+        {
+            post_fee_amount as u128 + self.maximum_fee as u128
+        } else {
+            raw_pre_fee_amount
+        };
+
+        u64::try_from(pre_fee_amount).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+}
+
+/// ========== 读取当前生效的转账手续费 ==========
+///
+/// 根据当前 epoch 从 mint 的 TransferFeeConfig 扩展中选出生效的一档费率；
+/// 没有该扩展时返回 [`TransferFee::ZERO`]。
+pub fn transfer_fee(mint: &AccountView) -> Result<TransferFee, ProgramError> {
+    let data = mint.try_borrow()?;
+
+    let Some((_, config)) = extensions(&data)
+        .find(|(extension_type, _)| *extension_type == EXTENSION_TRANSFER_FEE_CONFIG)
+    else {
+        return Ok(TransferFee::ZERO);
+    };
+    if config.len() < NEWER_TRANSFER_FEE_OFFSET + 18 {
+        return Err(AmmError::InvalidMint.into());
+    }
+
+    // 每档费率布局：[生效 epoch u64][手续费上限 u64][基点 u16]
+    let read_fee = |offset: usize| {
+        let epoch = u64::from_le_bytes(config[offset..offset + 8].try_into().unwrap());
+        let fee = TransferFee {
+            maximum_fee: u64::from_le_bytes(config[offset + 8..offset + 16].try_into().unwrap()),
+            basis_points: u16::from_le_bytes(config[offset + 16..offset + 18].try_into().unwrap()),
+        };
+        (epoch, fee)
+    };
+    let (newer_epoch, newer_fee) = read_fee(NEWER_TRANSFER_FEE_OFFSET);
+    let (_, older_fee) = read_fee(OLDER_TRANSFER_FEE_OFFSET);
+
+    if Clock::get()?.epoch >= newer_epoch {
+        Ok(newer_fee)
+    } else {
+        Ok(older_fee)
+    }
+}
+
+/// ========== TransferChecked 转账 ==========
+///
+/// 同时适用于 SPL Token 和 Token-2022 的 `TransferChecked` CPI。
+/// 与 `Transfer` 不同，它需要传入 mint 账户和精度，Token-2022 要求使用该指令。
+pub struct TransferChecked<'a, 'b> {
+    /// 转出的代币账户
+    pub from: &'a AccountView,
+    /// 代币的 mint 账户
+    pub mint: &'a AccountView,
+    /// 转入的代币账户
+    pub to: &'a AccountView,
+    /// 转出账户的所有者或委托人
+    pub authority: &'a AccountView,
+    /// 转账数量
+    pub amount: u64,
+    /// mint 的精度
+    pub decimals: u8,
+    /// 代币所属的 Token 程序
+    pub token_program: &'b Address,
+}
+
+impl TransferChecked<'_, '_> {
+    /// TransferChecked 在两种 Token 程序中的指令编号
+    const DISCRIMINATOR: u8 = 12;
+
+    /// 以普通签名调用
+    #[inline(always)]
+    pub fn invoke(&self) -> ProgramResult {
+        self.invoke_signed(&[])
+    }
+
+    /// 以 PDA 签名调用
+    pub fn invoke_signed(&self, signers: &[Signer]) -> ProgramResult {
+        let instruction_accounts = [
+            InstructionAccount::writable(self.from.address()),
+            InstructionAccount::readonly(self.mint.address()),
+            InstructionAccount::writable(self.to.address()),
+            InstructionAccount::readonly_signer(self.authority.address()),
+        ];
+
+        // 指令数据：[鉴别器 u8][数量 u64][精度 u8]
+        let mut instruction_data = [0u8; 10];
+        instruction_data[0] = Self::DISCRIMINATOR;
+        instruction_data[1..9].copy_from_slice(&self.amount.to_le_bytes());
+        instruction_data[9] = self.decimals;
+
+        let instruction = InstructionView {
+            program_id: self.token_program,
+            accounts: &instruction_accounts,
+            data: &instruction_data,
+        };
+
+        invoke_signed(
+            &instruction,
+            &[self.from, self.mint, self.to, self.authority],
+            signers,
+        )
+    }
+}